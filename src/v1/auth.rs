@@ -0,0 +1,437 @@
+use std::{
+    fmt,
+    path::Path,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use futures_util::future::BoxFuture;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use reqwest::{
+    header::{HeaderValue, AUTHORIZATION},
+    Method, RequestBuilder,
+};
+use tokio::sync::Mutex;
+use zeroize::{Zeroize, Zeroizing};
+
+use super::{
+    errors::{check_response, GenerativeAIError, Result},
+    http::{self, HttpClient},
+    traits::AuthProvider,
+    types::requests::RequestOptions,
+};
+
+pub(crate) const API_KEY_HEADER: &str = "x-goog-api-key";
+
+/// The OAuth2 token endpoint of Google, used by a [ServiceAccount] unless its key file sets another one.
+pub const DEFAULT_TOKEN_ENDPOINT: &str = "https://oauth2.googleapis.com/token";
+
+/// The OAuth2 scope requested by a [ServiceAccount] by default.
+pub const CLOUD_PLATFORM_SCOPE: &str = "https://www.googleapis.com/auth/cloud-platform";
+
+/// Lifetime of the JWT signed by a [ServiceAccount], the maximum accepted by Google.
+const JWT_LIFETIME: Duration = Duration::from_secs(3600);
+
+/// Access tokens are refreshed this long before they expire, so they don't expire in flight.
+/// The margin is capped at half the lifetime of short-lived tokens, so they are still cached.
+const REFRESH_MARGIN: Duration = Duration::from_secs(300);
+
+/// ApiKey is a Google API key, sent in the `x-goog-api-key` header.
+///
+/// Its value is redacted from [Debug] and [Display][fmt::Display] output, and zeroized on drop.
+#[derive(Clone)]
+pub struct ApiKey(String);
+
+impl ApiKey {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self(api_key.into())
+    }
+
+    /// The value of the key. Take care not to log it.
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AuthProvider for ApiKey {
+    fn authorize<'a>(
+        &'a self,
+        _client: &'a HttpClient,
+        _request_options: &'a RequestOptions,
+        request: RequestBuilder,
+    ) -> BoxFuture<'a, Result<RequestBuilder>> {
+        Box::pin(async move { Ok(sensitive_header(request, API_KEY_HEADER, &self.0)) })
+    }
+}
+
+impl From<String> for ApiKey {
+    fn from(api_key: String) -> Self {
+        Self(api_key)
+    }
+}
+
+impl From<&str> for ApiKey {
+    fn from(api_key: &str) -> Self {
+        Self(api_key.to_string())
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ApiKey(<redacted>)")
+    }
+}
+
+impl fmt::Display for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+impl Drop for ApiKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// BearerToken is an OAuth2 access token, sent in the `Authorization` header.
+///
+/// Its value is redacted from [Debug] and [Display][fmt::Display] output, and zeroized on drop.
+/// The token is used as is: it is up to the caller to refresh it, see [ServiceAccount] otherwise.
+#[derive(Clone)]
+pub struct BearerToken(String);
+
+impl BearerToken {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self(token.into())
+    }
+
+    /// The value of the token. Take care not to log it.
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AuthProvider for BearerToken {
+    fn authorize<'a>(
+        &'a self,
+        _client: &'a HttpClient,
+        _request_options: &'a RequestOptions,
+        request: RequestBuilder,
+    ) -> BoxFuture<'a, Result<RequestBuilder>> {
+        Box::pin(async move {
+            let value = Zeroizing::new(format!("Bearer {}", self.0));
+            Ok(sensitive_header(request, AUTHORIZATION.as_str(), &value))
+        })
+    }
+}
+
+impl From<String> for BearerToken {
+    fn from(token: String) -> Self {
+        Self(token)
+    }
+}
+
+impl From<&str> for BearerToken {
+    fn from(token: &str) -> Self {
+        Self(token.to_string())
+    }
+}
+
+impl fmt::Debug for BearerToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BearerToken(<redacted>)")
+    }
+}
+
+impl fmt::Display for BearerToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+impl Drop for BearerToken {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// ServiceAccount authenticates with a Google service account JSON key.
+///
+/// A JWT signed with the key is exchanged for an access token at the token endpoint.
+/// The access token is cached and shared by concurrent requests, and refreshed shortly before it expires.
+pub struct ServiceAccount {
+    client_email: String,
+    private_key_id: Option<String>,
+    encoding_key: EncodingKey,
+    token_endpoint: String,
+    scopes: Vec<String>,
+    token: Mutex<Option<CachedToken>>,
+}
+
+#[derive(Clone)]
+struct CachedToken {
+    token: BearerToken,
+    refresh_at: Instant,
+}
+
+impl ServiceAccount {
+    /// Load a service account from the content of its JSON key file.
+    pub fn from_json(json: &str) -> Result<Self> {
+        #[derive(serde::Deserialize)]
+        struct ServiceAccountKey {
+            client_email: String,
+            private_key: Zeroizing<String>,
+            private_key_id: Option<String>,
+            token_uri: Option<String>,
+        }
+
+        // The error message could quote the private key, only its position is reported.
+        let key = serde_json::from_str::<ServiceAccountKey>(json).map_err(|err| {
+            GenerativeAIError::Validation(format!(
+                "invalid service account key at line {} column {}",
+                err.line(),
+                err.column()
+            ))
+        })?;
+        let encoding_key =
+            EncodingKey::from_rsa_pem(key.private_key.as_bytes()).map_err(|err| {
+                GenerativeAIError::Validation(format!("invalid service account private key: {err}"))
+            })?;
+
+        Ok(Self {
+            client_email: key.client_email,
+            private_key_id: key.private_key_id,
+            encoding_key,
+            token_endpoint: key
+                .token_uri
+                .unwrap_or_else(|| DEFAULT_TOKEN_ENDPOINT.to_string()),
+            scopes: vec![CLOUD_PLATFORM_SCOPE.to_string()],
+            token: Mutex::new(None),
+        })
+    }
+
+    /// Load a service account from its JSON key file.
+    pub async fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let json = Zeroizing::new(tokio::fs::read_to_string(path).await?);
+        Self::from_json(&json)
+    }
+
+    /// Exchange tokens at the given endpoint instead of the `token_uri` of the key file, e.g. a local stand-in in tests.
+    pub fn with_token_endpoint(mut self, token_endpoint: impl Into<String>) -> Self {
+        self.token_endpoint = token_endpoint.into();
+        self
+    }
+
+    /// Request the given OAuth2 scopes instead of [CLOUD_PLATFORM_SCOPE].
+    pub fn with_scopes(mut self, scopes: Vec<String>) -> Self {
+        self.scopes = scopes;
+        self
+    }
+
+    /// The email address of the service account.
+    pub fn client_email(&self) -> &str {
+        &self.client_email
+    }
+
+    /// Get a valid access token, exchanging a new JWT when the cached one is missing or about to expire.
+    ///
+    /// The token exchange is sent with `request_options`, e.g. its timeout and retry policy.
+    pub async fn access_token(
+        &self,
+        client: &HttpClient,
+        request_options: &RequestOptions,
+    ) -> Result<BearerToken> {
+        let mut cached = self.token.lock().await;
+        if let Some(cached) = cached
+            .as_ref()
+            .filter(|cached| cached.refresh_at > Instant::now())
+        {
+            return Ok(cached.token.clone());
+        }
+
+        let token = self._fetch_token(client, request_options).await?;
+        *cached = Some(token.clone());
+        Ok(token.token)
+    }
+
+    async fn _fetch_token(
+        &self,
+        client: &HttpClient,
+        request_options: &RequestOptions,
+    ) -> Result<CachedToken> {
+        #[derive(serde::Serialize)]
+        struct Claims<'a> {
+            iss: &'a str,
+            scope: String,
+            aud: &'a str,
+            iat: u64,
+            exp: u64,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct TokenResponse {
+            access_token: String,
+            expires_in: u64,
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let claims = Claims {
+            iss: &self.client_email,
+            scope: self.scopes.join(" "),
+            aud: &self.token_endpoint,
+            iat: now,
+            exp: now + JWT_LIFETIME.as_secs(),
+        };
+        let header = Header {
+            kid: self.private_key_id.clone(),
+            ..Header::new(Algorithm::RS256)
+        };
+        let assertion = Zeroizing::new(
+            jsonwebtoken::encode(&header, &claims, &self.encoding_key).map_err(|err| {
+                GenerativeAIError::Validation(format!("cannot sign service account JWT: {err}"))
+            })?,
+        );
+
+        let requested_at = Instant::now();
+        let request = client.request(Method::POST, &self.token_endpoint).form(&[
+            ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+            ("assertion", assertion.as_str()),
+        ]);
+        let response = http::send(client, request, request_options).await?;
+        // The body holds the access token, so it is not attached to decoding errors.
+        let response: TokenResponse = check_response(response).await?.json().await?;
+
+        let lifetime = Duration::from_secs(response.expires_in);
+        Ok(CachedToken {
+            token: BearerToken(response.access_token),
+            refresh_at: requested_at + lifetime - REFRESH_MARGIN.min(lifetime / 2),
+        })
+    }
+}
+
+impl AuthProvider for ServiceAccount {
+    fn authorize<'a>(
+        &'a self,
+        client: &'a HttpClient,
+        request_options: &'a RequestOptions,
+        request: RequestBuilder,
+    ) -> BoxFuture<'a, Result<RequestBuilder>> {
+        Box::pin(async move {
+            let token = self.access_token(client, request_options).await?;
+            token.authorize(client, request_options, request).await
+        })
+    }
+}
+
+impl fmt::Debug for ServiceAccount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServiceAccount")
+            .field("client_email", &self.client_email)
+            .field("token_endpoint", &self.token_endpoint)
+            .field("scopes", &self.scopes)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Add a header marked sensitive, so it is redacted from the [Debug] output of the request.
+fn sensitive_header(request: RequestBuilder, name: &str, value: &str) -> RequestBuilder {
+    match HeaderValue::from_str(value) {
+        Ok(mut value) => {
+            value.set_sensitive(true);
+            request.header(name, value)
+        }
+        // Let the builder fail, its error does not include the value.
+        Err(_) => request.header(name, value),
+    }
+}
+
+#[tokio::test]
+async fn test_api_key() {
+    let api_key = ApiKey::from("AIza-secret");
+    assert_eq!(format!("{api_key:?}"), "ApiKey(<redacted>)");
+    assert_eq!(api_key.to_string(), "<redacted>");
+
+    let client = HttpClient::default();
+    let request = client.request(
+        Method::GET,
+        "https://generativelanguage.googleapis.com/v1beta/models",
+    );
+    let request = api_key
+        .authorize(&client, &RequestOptions::default(), request)
+        .await
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(request.url().query(), None);
+    assert_eq!(request.headers()[API_KEY_HEADER], "AIza-secret");
+    assert!(request.headers()[API_KEY_HEADER].is_sensitive());
+    assert!(!format!("{request:?}").contains("AIza-secret"));
+}
+
+#[tokio::test]
+async fn test_service_account() {
+    use std::sync::{Arc, Mutex};
+
+    use crate::v1::traits::Transport;
+
+    #[derive(Debug, Default)]
+    struct FakeTokenEndpoint {
+        requests: Arc<Mutex<Vec<reqwest::Request>>>,
+    }
+
+    impl Transport for FakeTokenEndpoint {
+        fn execute(&self, request: reqwest::Request) -> BoxFuture<'_, Result<reqwest::Response>> {
+            self.requests.lock().unwrap().push(request);
+            // Shorter than twice the refresh margin, the token is still cached.
+            let response = ::http::Response::new(
+                r#"{"access_token": "ya29.token", "expires_in": 120, "token_type": "Bearer"}"#,
+            );
+            Box::pin(async move { Ok(response.into()) })
+        }
+    }
+
+    let service_account = ServiceAccount::from_json(crate::v1::testing::SERVICE_ACCOUNT_KEY)
+        .unwrap()
+        .with_token_endpoint("http://localhost/token");
+    assert!(!format!("{service_account:?}").contains("PRIVATE KEY"));
+
+    let transport = FakeTokenEndpoint::default();
+    let requests = transport.requests.clone();
+    let client = HttpClient::from_transport(transport);
+    let request_options = RequestOptions {
+        custom_headers: Some(std::collections::HashMap::from([(
+            "x-custom".to_string(),
+            "value".to_string(),
+        )])),
+        ..Default::default()
+    };
+    for _ in 0..2 {
+        let request = client.request(Method::GET, "http://localhost/v1/models");
+        let request = service_account
+            .authorize(&client, &request_options, request)
+            .await
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(request.headers()[AUTHORIZATION], "Bearer ya29.token");
+    }
+
+    // The token is cached, so it was exchanged only once.
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].url().as_str(), "http://localhost/token");
+    assert_eq!(requests[0].headers()["x-custom"], "value");
+    let body = std::str::from_utf8(requests[0].body().unwrap().as_bytes().unwrap()).unwrap();
+    let assertion = body.split_once("&assertion=").unwrap().1;
+    let header = jsonwebtoken::decode_header(assertion).unwrap();
+    assert_eq!(header.alg, Algorithm::RS256);
+    assert_eq!(header.kid.as_deref(), Some("key-id"));
+
+    assert!(matches!(
+        ServiceAccount::from_json(r#"{"client_email": "sdk", "private_key": "not a key"}"#),
+        Err(GenerativeAIError::Validation(_))
+    ));
+}
@@ -0,0 +1,351 @@
+use std::{collections::HashMap, fmt, time::Duration};
+
+use reqwest::{Response, StatusCode};
+use serde::de::DeserializeOwned;
+
+use super::{server::file_manager::FileProcessingError, types::responses::PromptFeedback};
+
+pub type Result<T> = std::result::Result<T, GenerativeAIError>;
+
+/// GenerativeAIError is the error returned by every fallible operation of this SDK.
+#[derive(Debug)]
+pub enum GenerativeAIError {
+    /// The request could not be sent, or the response could not be received.
+    Transport(reqwest::Error),
+
+    /// The API answered with an error status.
+    Api(ApiError),
+
+    /// The response body could not be decoded. The raw body is attached.
+    Decode {
+        source: serde_json::Error,
+        body: String,
+    },
+
+    /// The prompt was blocked and no candidates were returned, see [PromptFeedback::block_reason].
+    PromptBlocked(Box<PromptFeedback>),
+
+    /// No response, or no chunk of a streamed response, was received within [RequestOptions::timeout][crate::v1::types::requests::RequestOptions::timeout].
+    Timeout(Duration),
+
+    /// The response was incomplete, e.g. a stream ended early or without any response.
+    IncompleteResponse(String),
+
+    /// The input was rejected before sending any request.
+    Validation(String),
+
+    /// Reading a local file failed.
+    Io(std::io::Error),
+
+    /// An uploaded file did not become active.
+    FileProcessing(FileProcessingError),
+
+    /// A cassette of the `testing` feature could not be read, or has no recorded interaction for a request.
+    Cassette(String),
+}
+
+impl fmt::Display for GenerativeAIError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transport(err) => write!(f, "error sending request: {err}"),
+            Self::Api(err) => err.fmt(f),
+            Self::Decode { source, body } => {
+                write!(f, "error decoding response body: {source}, body: {body}")
+            }
+            Self::PromptBlocked(feedback) => {
+                write!(f, "prompt was blocked")?;
+                if let Some(block_reason) = &feedback.block_reason {
+                    write!(f, ": {block_reason:?}")?;
+                }
+                if let Some(message) = &feedback.block_reason_message {
+                    write!(f, ", {message}")?;
+                }
+                Ok(())
+            }
+            Self::Timeout(timeout) => write!(f, "no response received within {timeout:?}"),
+            Self::IncompleteResponse(message) => write!(f, "incomplete response: {message}"),
+            Self::Validation(message) => write!(f, "invalid request: {message}"),
+            Self::Io(err) => write!(f, "error reading file: {err}"),
+            Self::FileProcessing(err) => err.fmt(f),
+            Self::Cassette(message) => write!(f, "cassette error: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for GenerativeAIError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Transport(err) => Some(err),
+            Self::Decode { source, .. } => Some(source),
+            Self::Io(err) => Some(err),
+            Self::FileProcessing(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for GenerativeAIError {
+    fn from(err: reqwest::Error) -> Self {
+        Self::Transport(err)
+    }
+}
+
+impl From<std::io::Error> for GenerativeAIError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<FileProcessingError> for GenerativeAIError {
+    fn from(err: FileProcessingError) -> Self {
+        Self::FileProcessing(err)
+    }
+}
+
+/// ApiError is an error answered by the API, parsed from a `{"error": {"code", "message", "status", "details"}}` body.
+#[derive(Debug, Clone)]
+pub struct ApiError {
+    /// The HTTP status of the response.
+    pub http_status: StatusCode,
+
+    /// The error code, usually the same as the HTTP status.
+    pub code: Option<u16>,
+
+    /// A developer-facing error message.
+    pub message: String,
+
+    /// The canonical status, e.g. `INVALID_ARGUMENT` or `RESOURCE_EXHAUSTED`.
+    pub status: Option<String>,
+
+    /// Additional details about the error.
+    pub details: Vec<ErrorDetail>,
+}
+
+impl ApiError {
+    /// Build an [ApiError] from an error status and body. Bodies which are not a Google error are kept as the message.
+    pub(crate) fn from_body(http_status: StatusCode, body: &str) -> Self {
+        #[derive(serde::Deserialize)]
+        struct ErrorBody {
+            error: ErrorStatus,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct ErrorStatus {
+            code: Option<u16>,
+            #[serde(default)]
+            message: String,
+            status: Option<String>,
+            #[serde(default)]
+            details: Vec<serde_json::Value>,
+        }
+
+        match serde_json::from_str::<ErrorBody>(body) {
+            Ok(ErrorBody { error }) => Self {
+                http_status,
+                code: error.code,
+                message: error.message,
+                status: error.status,
+                details: error.details.into_iter().map(ErrorDetail::from).collect(),
+            },
+            Err(_) => Self {
+                http_status,
+                code: None,
+                message: body.to_string(),
+                status: None,
+                details: Vec::new(),
+            },
+        }
+    }
+
+    /// The [ErrorInfo] detail, if any.
+    pub fn error_info(&self) -> Option<&ErrorInfo> {
+        self.details.iter().find_map(|detail| match detail {
+            ErrorDetail::ErrorInfo(info) => Some(info),
+            _ => None,
+        })
+    }
+
+    /// The field violations of the [BadRequest] details.
+    pub fn field_violations(&self) -> impl Iterator<Item = &FieldViolation> {
+        self.details
+            .iter()
+            .filter_map(|detail| match detail {
+                ErrorDetail::BadRequest(bad_request) => Some(&bad_request.field_violations),
+                _ => None,
+            })
+            .flatten()
+    }
+
+    /// The delay the server asked to wait before retrying, from the [RetryInfo] detail.
+    pub fn retry_delay(&self) -> Option<Duration> {
+        self.details.iter().find_map(|detail| match detail {
+            ErrorDetail::RetryInfo(info) => info.retry_delay(),
+            _ => None,
+        })
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "API error {}", self.http_status)?;
+        if let Some(status) = &self.status {
+            write!(f, " {status}")?;
+        }
+        write!(f, ": {}", self.message)?;
+        for violation in self.field_violations() {
+            write!(f, "; {}: {}", violation.field, violation.description)?;
+        }
+        Ok(())
+    }
+}
+
+/// ErrorDetail is a detail of an [ApiError], identified by its `@type`.
+#[derive(Debug, Clone)]
+pub enum ErrorDetail {
+    /// `type.googleapis.com/google.rpc.ErrorInfo`
+    ErrorInfo(ErrorInfo),
+
+    /// `type.googleapis.com/google.rpc.BadRequest`
+    BadRequest(BadRequest),
+
+    /// `type.googleapis.com/google.rpc.RetryInfo`
+    RetryInfo(RetryInfo),
+
+    /// Any other detail, kept as is.
+    Other(serde_json::Value),
+}
+
+impl From<serde_json::Value> for ErrorDetail {
+    fn from(value: serde_json::Value) -> Self {
+        fn parse<T: DeserializeOwned>(value: &serde_json::Value) -> Option<T> {
+            serde_json::from_value(value.clone()).ok()
+        }
+
+        let detail = match value.get("@type").and_then(|t| t.as_str()) {
+            Some("type.googleapis.com/google.rpc.ErrorInfo") => parse(&value).map(Self::ErrorInfo),
+            Some("type.googleapis.com/google.rpc.BadRequest") => {
+                parse(&value).map(Self::BadRequest)
+            }
+            Some("type.googleapis.com/google.rpc.RetryInfo") => parse(&value).map(Self::RetryInfo),
+            _ => None,
+        };
+        detail.unwrap_or(Self::Other(value))
+    }
+}
+
+/// ErrorInfo describes the cause of the error with structured details.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct ErrorInfo {
+    /// The reason of the error, e.g. `API_KEY_INVALID`.
+    #[serde(default)]
+    pub reason: String,
+
+    /// The logical grouping to which the reason belongs, e.g. `googleapis.com`.
+    #[serde(default)]
+    pub domain: String,
+
+    /// Additional structured details about this error.
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+/// BadRequest describes violations in a client request.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BadRequest {
+    /// Describes all violations in a client request.
+    #[serde(default)]
+    pub field_violations: Vec<FieldViolation>,
+}
+
+/// FieldViolation is a single bad request field.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct FieldViolation {
+    /// A path that leads to a field in the request body.
+    #[serde(default)]
+    pub field: String,
+
+    /// A description of why the request element is bad.
+    #[serde(default)]
+    pub description: String,
+}
+
+/// RetryInfo describes when the client can retry a failed request.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryInfo {
+    /// Minimum delay to wait before retrying, a duration in seconds ending with `s`. E.g. `3.5s`.
+    pub retry_delay: Option<String>,
+}
+
+impl RetryInfo {
+    /// [RetryInfo::retry_delay] as a [Duration].
+    pub fn retry_delay(&self) -> Option<Duration> {
+        let seconds = self.retry_delay.as_deref()?.strip_suffix('s')?;
+        Duration::try_from_secs_f64(seconds.parse().ok()?).ok()
+    }
+}
+
+/// Turn an unsuccessful response into an [ApiError].
+pub(crate) async fn check_response(response: Response) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let body = response.text().await?;
+    Err(GenerativeAIError::Api(ApiError::from_body(status, &body)))
+}
+
+/// Check the status of a response, then decode its JSON body.
+pub(crate) async fn decode_response<T: DeserializeOwned>(response: Response) -> Result<T> {
+    let body = check_response(response).await?.text().await?;
+    decode_body(body)
+}
+
+/// Decode a JSON body, attaching it to the error on failure.
+pub(crate) fn decode_body<T: DeserializeOwned>(body: String) -> Result<T> {
+    serde_json::from_str(&body).map_err(|source| GenerativeAIError::Decode { source, body })
+}
+
+#[test]
+fn test_api_error_from_body() {
+    let body = r#"{
+        "error": {
+            "code": 429,
+            "message": "Resource has been exhausted (e.g. check quota).",
+            "status": "RESOURCE_EXHAUSTED",
+            "details": [
+                {
+                    "@type": "type.googleapis.com/google.rpc.ErrorInfo",
+                    "reason": "RATE_LIMIT_EXCEEDED",
+                    "domain": "googleapis.com",
+                    "metadata": { "service": "generativelanguage.googleapis.com" }
+                },
+                {
+                    "@type": "type.googleapis.com/google.rpc.BadRequest",
+                    "fieldViolations": [
+                        { "field": "contents[0].parts", "description": "must not be empty" }
+                    ]
+                },
+                { "@type": "type.googleapis.com/google.rpc.RetryInfo", "retryDelay": "1.5s" },
+                { "@type": "type.googleapis.com/google.rpc.Help", "links": [] }
+            ]
+        }
+    }"#;
+
+    let err = ApiError::from_body(StatusCode::TOO_MANY_REQUESTS, body);
+    assert_eq!(err.code, Some(429));
+    assert_eq!(err.status.as_deref(), Some("RESOURCE_EXHAUSTED"));
+    assert_eq!(err.error_info().unwrap().reason, "RATE_LIMIT_EXCEEDED");
+    assert_eq!(err.field_violations().count(), 1);
+    assert_eq!(err.retry_delay(), Some(Duration::from_millis(1500)));
+    assert!(matches!(err.details[3], ErrorDetail::Other(_)));
+    assert!(err
+        .to_string()
+        .contains("contents[0].parts: must not be empty"));
+
+    let err = ApiError::from_body(StatusCode::BAD_GATEWAY, "<html>Bad Gateway</html>");
+    assert_eq!(err.message, "<html>Bad Gateway</html>");
+    assert!(err.details.is_empty());
+}
@@ -1,36 +1,101 @@
+use std::sync::Arc;
+
 use super::{
+    auth::ApiKey,
+    errors::{GenerativeAIError, Result},
+    http::HttpClient,
     models::{generative_models::GenerativeModel, get_model_info, get_model_list},
+    server::{cache_manager::CacheManager, file_manager::FileManager},
+    traits::AuthProvider,
     types::{
         model::{Model, ModelParams},
-        requests::RequestOptions,
+        requests::{Backend, RequestOptions},
         responses::ListModelResponse,
+        server::caching::CachedContent,
     },
 };
 
 /// Top-level class for this SDK
+///
+/// It owns one [HttpClient], shared by every model and manager it creates.
 #[derive(Debug)]
 pub struct GoogleGenerativeAI {
-    api_key: String,
+    auth: Arc<dyn AuthProvider>,
+    client: HttpClient,
+    backend: Backend,
 }
 
 impl GoogleGenerativeAI {
-    pub fn new(api_key: String) -> Self {
-        Self { api_key }
+    pub fn new(api_key: impl Into<ApiKey>) -> Self {
+        Self::with_http_client(api_key, HttpClient::default())
+    }
+
+    /// Create an instance sending its requests through the given client.
+    /// Use [HttpClient::from_client] to configure the underlying [reqwest::Client].
+    pub fn with_http_client(api_key: impl Into<ApiKey>, client: HttpClient) -> Self {
+        Self::with_auth_provider(api_key.into(), client)
+    }
+
+    /// Create an instance authenticating its requests with the given provider,
+    /// e.g. a [BearerToken][crate::v1::auth::BearerToken] or a [ServiceAccount][crate::v1::auth::ServiceAccount].
+    pub fn with_auth_provider(auth: impl AuthProvider + 'static, client: HttpClient) -> Self {
+        Self {
+            auth: Arc::new(auth),
+            client,
+            backend: Backend::default(),
+        }
+    }
+
+    /// Send the requests to the given backend.
+    ///
+    /// The authentication is kept as is. Vertex AI expects an OAuth2 token and only accepts an [ApiKey] in express mode,
+    /// so create the instance with [GoogleGenerativeAI::with_auth_provider] otherwise.
+    /// Embeddings are not available on Vertex AI, see [GenerativeModel::embed_content].
+    ///
+    /// For instance, to use Vertex AI with a service account:
+    /// ```no_run
+    /// use google_generative_ai_rs::v1::{
+    ///     auth::ServiceAccount, genai::GoogleGenerativeAI, http::HttpClient, types::requests::Backend,
+    /// };
+    ///
+    /// # async fn run() -> google_generative_ai_rs::v1::errors::Result<()> {
+    /// let service_account = ServiceAccount::from_file("service-account.json").await?;
+    /// let genai = GoogleGenerativeAI::with_auth_provider(service_account, HttpClient::default())
+    ///     .with_backend(Backend::vertex_ai("my-project", "us-central1"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
     }
 
     pub async fn get_model_list(
         &self,
         request_options: Option<RequestOptions>,
-    ) -> Result<ListModelResponse, reqwest::Error> {
-        get_model_list(self.api_key.clone(), request_options).await
+    ) -> Result<ListModelResponse> {
+        get_model_list(
+            &self.client,
+            self.auth.as_ref(),
+            &self.backend,
+            request_options,
+        )
+        .await
     }
 
     pub async fn get_model_info(
         &self,
         model: String,
         request_options: Option<RequestOptions>,
-    ) -> Result<Model, reqwest::Error> {
-        get_model_info(self.api_key.clone(), model, request_options).await
+    ) -> Result<Model> {
+        get_model_info(
+            &self.client,
+            self.auth.as_ref(),
+            &self.backend,
+            model,
+            request_options,
+        )
+        .await
     }
 
     /// Gets a GenerativeModel instance for the provided model name.
@@ -39,75 +104,297 @@ impl GoogleGenerativeAI {
         model_params: ModelParams,
         request_options: Option<RequestOptions>,
     ) -> GenerativeModel {
-        GenerativeModel::new(self.api_key.clone(), model_params, request_options)
+        GenerativeModel::from_auth_provider(self.auth.clone(), model_params, request_options)
+            .with_http_client(self.client.clone())
+            .with_backend(self.backend.clone())
+    }
+
+    /// Gets a GenerativeModel instance using the provided cached content.
+    ///
+    /// The model, system instruction, tools and tool config are inherited from the cache.
+    /// `model_params` may be provided for the other settings, such as the generation config,
+    /// but its model must then be empty or match the model of the cache.
+    pub fn get_generative_model_from_cached_content(
+        &self,
+        cached_content: &CachedContent,
+        model_params: Option<ModelParams>,
+        request_options: Option<RequestOptions>,
+    ) -> Result<GenerativeModel> {
+        if cached_content.name.is_none() {
+            return Err(GenerativeAIError::Validation(
+                "cached content must contain a `name` field".to_string(),
+            ));
+        }
+        if cached_content.model.is_empty() {
+            return Err(GenerativeAIError::Validation(
+                "cached content must contain a `model` field".to_string(),
+            ));
+        }
+
+        let model_params = model_params.unwrap_or_default();
+        let model_name = |model: &str| model.trim_start_matches("models/").to_string();
+        if !model_params.model.is_empty()
+            && model_name(&model_params.model) != model_name(&cached_content.model)
+        {
+            return Err(GenerativeAIError::Validation(format!(
+                "different value for `model` parameter: {} and cached content: {}",
+                model_params.model, cached_content.model
+            )));
+        }
+
+        let model_params = ModelParams {
+            model: cached_content.model.clone(),
+            system_instruction: cached_content.system_instruction.clone(),
+            tools: cached_content.tools.clone(),
+            tool_config: cached_content.tool_config.clone(),
+            cached_content: Some(cached_content.clone()),
+            ..model_params
+        };
+
+        Ok(
+            GenerativeModel::from_auth_provider(self.auth.clone(), model_params, request_options)
+                .with_http_client(self.client.clone())
+                .with_backend(self.backend.clone()),
+        )
+    }
+
+    /// Gets a CacheManager instance to create and manage cached contents.
+    pub fn get_cache_manager(&self, request_options: Option<RequestOptions>) -> CacheManager {
+        CacheManager::from_auth_provider(self.auth.clone(), request_options)
+            .with_http_client(self.client.clone())
+    }
+
+    /// Gets a FileManager instance to upload and manage files.
+    pub fn get_file_manager(&self, request_options: Option<RequestOptions>) -> FileManager {
+        FileManager::from_auth_provider(self.auth.clone(), request_options)
+            .with_http_client(self.client.clone())
     }
 }
 
-// #[tokio::test]
-// async fn test_get_model_list() {
-//     dotenvy::dotenv().ok();
-//     let api_key = std::env::var("GOOGLE_API_KEY").expect(".env not found");
-
-//     let model_list = GoogleGenerativeAI::new(api_key).get_model_list(None).await;
-//     if let Err(err) = model_list {
-//         panic!("{err}");
-//     }
-// }
-
-// #[tokio::test]
-// async fn test_get_model_info() {
-//     dotenvy::dotenv().ok();
-//     let api_key = std::env::var("GOOGLE_API_KEY").expect(".env not found");
-
-//     let model_list = GoogleGenerativeAI::new(api_key)
-//         .get_model_info(String::from("gemini-pro"), None)
-//         .await;
-//     if let Err(err) = model_list {
-//         panic!("{err}");
-//     }
-// }
-
-// #[test]
-// fn test_construct_google_genai() {
-//     dotenvy::dotenv().ok();
-//     let api_key = std::env::var("GOOGLE_API_KEY").expect(".env not found");
-
-//     let _ = GoogleGenerativeAI::new(api_key)
-//         .get_generative_model(ModelParams::new("gemini-1.5-flash"), None);
-// }
-
-// #[tokio::test]
-// async fn test_generate_content() {
-//     dotenvy::dotenv().ok();
-//     let api_key = std::env::var("GOOGLE_API_KEY").expect(".env not found");
-
-//     let genai = GoogleGenerativeAI::new(api_key)
-//         .get_generative_model(ModelParams::new("gemini-1.5-flash"), None);
-//     let content = genai
-//         .generate_content(vec![crate::v1::types::content_types::Part {
-//             text: Some("Hello Gemini".to_string()),
-//             ..Default::default()
-//         }])
-//         .await;
-//     if let Err(err) = content {
-//         panic!("{err}");
-//     }
-// }
+#[tokio::test]
+async fn test_get_model_list() {
+    use crate::v1::testing::{model_json, MockServer};
+
+    let server = MockServer::start().await.unwrap();
+    server.mock_models(vec![
+        model_json("gemini-1.5-flash"),
+        model_json("gemini-1.5-pro"),
+    ]);
+
+    let model_list = GoogleGenerativeAI::new("api-key")
+        .get_model_list(Some(server.request_options()))
+        .await
+        .unwrap();
+    assert_eq!(model_list.models.len(), 2);
+    assert_eq!(model_list.models[0].name, "models/gemini-1.5-flash");
+
+    let requests = server.requests();
+    assert_eq!(requests[0].path, "/v1beta/models");
+    assert_eq!(requests[0].header("x-goog-api-key"), Some("api-key"));
+}
+
+#[tokio::test]
+async fn test_get_model_info() {
+    use crate::v1::testing::MockServer;
+
+    let server = MockServer::start().await.unwrap();
+    server.mock_model_info("gemini-pro");
+
+    let model = GoogleGenerativeAI::new("api-key")
+        .get_model_info(String::from("gemini-pro"), Some(server.request_options()))
+        .await
+        .unwrap();
+    assert_eq!(model.name, "models/gemini-pro");
+    assert_eq!(model.output_token_limit, 8192);
+}
+
+#[tokio::test]
+async fn test_generate_content() {
+    use crate::v1::testing::{text_response, MockServer};
+
+    let server = MockServer::start().await.unwrap();
+    server.mock_generate_content("gemini-1.5-flash", text_response("Hello!"));
+
+    let genai = GoogleGenerativeAI::new("api-key").get_generative_model(
+        ModelParams::new("gemini-1.5-flash"),
+        Some(server.request_options()),
+    );
+    let content = genai.generate_content("Hello Gemini").await.unwrap();
+    let text = content.candidates[0].content.as_ref().unwrap().parts[0].as_text();
+    assert_eq!(text, Some("Hello!"));
+
+    let request = server.requests()[0].json();
+    assert_eq!(request["contents"][0]["parts"][0]["text"], "Hello Gemini");
+}
 
 #[tokio::test]
 async fn test_generate_content_stream() {
-    dotenvy::dotenv().ok();
-    let api_key = std::env::var("GOOGLE_API_KEY").expect(".env not found");
-
-    let genai = GoogleGenerativeAI::new(api_key)
-        .get_generative_model(ModelParams::new("gemini-1.5-flash"), None);
-    let content = genai
-        .generate_content_stream(vec![crate::v1::types::content_types::Part {
-            text: Some("Hello Gemini. Can you tell me something about you?".to_string()),
+    use futures_util::StreamExt;
+
+    use crate::v1::testing::{text_response, MockServer};
+
+    let server = MockServer::start().await.unwrap();
+    server.mock_stream_generate_content(
+        "gemini-1.5-flash",
+        vec![text_response("Hello"), text_response(", I am Gemini.")],
+    );
+
+    let genai = GoogleGenerativeAI::new("api-key").get_generative_model(
+        ModelParams::new("gemini-1.5-flash"),
+        Some(server.request_options()),
+    );
+    let mut stream = genai
+        .generate_content_stream("Hello Gemini. Can you tell me something about you?")
+        .await
+        .unwrap();
+    let mut chunks = 0;
+    while let Some(chunk) = stream.next().await {
+        chunk.unwrap();
+        chunks += 1;
+    }
+    assert_eq!(chunks, 2);
+
+    let response = stream.response().await.unwrap();
+    let text = response.candidates[0].content.as_ref().unwrap().parts[0].as_text();
+    assert_eq!(text, Some("Hello, I am Gemini."));
+    assert_eq!(server.requests()[0].query.as_deref(), Some("alt=sse"));
+}
+
+#[tokio::test]
+async fn test_api_error() {
+    use reqwest::{Method, StatusCode};
+
+    use crate::v1::{
+        errors::GenerativeAIError,
+        testing::{text_response, MockServer},
+        types::{content_types::Part, requests::RetryPolicy},
+    };
+
+    let server = MockServer::start().await.unwrap();
+    server.mock_error(
+        Method::POST,
+        "models/gemini-1.5-flash:generateContent",
+        StatusCode::SERVICE_UNAVAILABLE,
+        "The model is overloaded.",
+    );
+    server.mock_generate_content("gemini-1.5-flash", text_response("Hello!"));
+    server.mock_error(
+        Method::POST,
+        "models/gemini-1.5-flash:generateContent",
+        StatusCode::BAD_REQUEST,
+        "API key not valid.",
+    );
+
+    let request_options = RequestOptions {
+        retry_policy: Some(RetryPolicy {
+            base_delay: std::time::Duration::from_millis(1),
             ..Default::default()
-        }])
-        .await;
-    if let Err(err) = content {
-        panic!("{err}");
+        }),
+        ..server.request_options()
+    };
+    let genai = GoogleGenerativeAI::new("api-key")
+        .get_generative_model(ModelParams::new("gemini-1.5-flash"), Some(request_options));
+    let part = Part::from("Hello Gemini");
+
+    // The 503 is retried, the 400 is not.
+    genai.generate_content(vec![part.clone()]).await.unwrap();
+    let err = genai.generate_content(vec![part]).await.unwrap_err();
+    let GenerativeAIError::Api(err) = err else {
+        panic!("unexpected error: {err}");
+    };
+    assert_eq!(err.http_status, StatusCode::BAD_REQUEST);
+    assert_eq!(err.status.as_deref(), Some("INVALID_ARGUMENT"));
+    assert_eq!(server.requests().len(), 3);
+    assert_eq!(server.pending_fixtures(), 0);
+}
+
+#[tokio::test]
+async fn test_prompt_blocked() {
+    use crate::v1::{errors::GenerativeAIError, testing::MockServer};
+
+    let server = MockServer::start().await.unwrap();
+    server.mock_generate_content(
+        "gemini-1.5-flash",
+        serde_json::json!({
+            "promptFeedback": { "blockReason": "SAFETY", "safetyRatings": [] },
+            "usageMetadata": { "promptTokenCount": 4, "totalTokenCount": 4 }
+        }),
+    );
+
+    let genai = GoogleGenerativeAI::new("api-key").get_generative_model(
+        ModelParams::new("gemini-1.5-flash"),
+        Some(server.request_options()),
+    );
+    let err = genai.generate_content("Hello Gemini").await.unwrap_err();
+    assert!(matches!(err, GenerativeAIError::PromptBlocked(_)));
+    assert_eq!(err.to_string(), "prompt was blocked: Safety");
+}
+
+#[tokio::test]
+async fn test_embed_content() {
+    use crate::v1::{testing::MockServer, types::content_types::Part};
+
+    let server = MockServer::start().await.unwrap();
+    server.mock_embed_content("text-embedding-004", vec![0.1, 0.2]);
+    server.mock_count_tokens("text-embedding-004", 2);
+
+    let genai = GoogleGenerativeAI::new("api-key").get_generative_model(
+        ModelParams::new("text-embedding-004"),
+        Some(server.request_options()),
+    );
+    let part = Part::from("Hello Gemini");
+    let response = genai.embed_content(vec![part.clone()]).await.unwrap();
+    assert_eq!(response.embedding.values, [0.1, 0.2]);
+    assert_eq!(
+        genai.count_tokens(vec![part]).await.unwrap().total_tokens,
+        2
+    );
+}
+
+#[tokio::test]
+async fn test_models_share_http_client() {
+    use std::sync::{Arc, Mutex};
+
+    use futures_util::future::BoxFuture;
+
+    use crate::v1::{traits::Transport, types::content_types::Part};
+
+    #[derive(Debug, Default)]
+    struct FakeTransport {
+        urls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Transport for FakeTransport {
+        fn execute(&self, request: reqwest::Request) -> BoxFuture<'_, Result<reqwest::Response>> {
+            self.urls
+                .lock()
+                .unwrap()
+                .push(request.url().path().to_string());
+            let response = http::Response::new(r#"{"totalTokens": 3}"#);
+            Box::pin(async move { Ok(response.into()) })
+        }
+    }
+
+    let transport = FakeTransport::default();
+    let urls = transport.urls.clone();
+    let genai = GoogleGenerativeAI::with_http_client(
+        "api-key".to_string(),
+        HttpClient::from_transport(transport),
+    );
+
+    for model in ["gemini-1.5-flash", "gemini-1.5-pro"] {
+        let response = genai
+            .get_generative_model(ModelParams::new(model), None)
+            .count_tokens(vec![Part::from("Hello")])
+            .await
+            .unwrap();
+        assert_eq!(response.total_tokens, 3);
     }
+    assert_eq!(
+        *urls.lock().unwrap(),
+        [
+            "/v1beta/models/gemini-1.5-flash:countTokens",
+            "/v1beta/models/gemini-1.5-pro:countTokens"
+        ]
+    );
 }
@@ -0,0 +1,486 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use bytes::Bytes;
+use futures_util::{stream, Stream, StreamExt};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, RETRY_AFTER},
+    IntoUrl, Method, Request, RequestBuilder, Response,
+};
+
+use super::{
+    errors::{ApiError, GenerativeAIError, Result},
+    traits::Transport,
+    types::requests::{RequestOptions, RetryPolicy},
+};
+
+/// HttpClient sends the requests of [GoogleGenerativeAI][crate::v1::genai::GoogleGenerativeAI] and
+/// of the models and managers it creates.
+///
+/// Cloning it is cheap: clones share the same connection pool.
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    /// Client used to build requests, and to send them when no other transport is given.
+    client: reqwest::Client,
+    transport: Arc<dyn Transport>,
+}
+
+impl HttpClient {
+    /// Send requests through the given client, e.g. to configure a proxy, a custom CA or HTTP/2.
+    pub fn from_client(client: reqwest::Client) -> Self {
+        Self {
+            transport: Arc::new(client.clone()),
+            client,
+        }
+    }
+
+    /// Send requests through the given [Transport].
+    pub fn from_transport(transport: impl Transport + 'static) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            transport: Arc::new(transport),
+        }
+    }
+
+    pub(crate) fn request(&self, method: Method, url: impl IntoUrl) -> RequestBuilder {
+        self.client.request(method, url)
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::from_client(reqwest::Client::new())
+    }
+}
+
+const API_CLIENT_HEADER: &str = "x-goog-api-client";
+
+/// Value of the `x-goog-api-client` header identifying this SDK.
+const API_CLIENT: &str = concat!("genai-rs/", env!("CARGO_PKG_VERSION"));
+
+/// Send a request, retrying it according to [RequestOptions::retry_policy].
+///
+/// [RequestOptions::timeout] applies to each attempt as a whole, including reading the response body.
+///
+/// Only failures which happen before a response is received are retried: a response with a status that is not
+/// retryable is returned as is. Requests with a streaming body cannot be cloned, so they are sent exactly once.
+///
+/// When the last attempt fails with a retryable status, its body has been read to find the retry delay,
+/// so the error is returned as a [GenerativeAIError::Api].
+pub(crate) async fn send(
+    client: &HttpClient,
+    request: RequestBuilder,
+    request_options: &RequestOptions,
+) -> Result<Response> {
+    let request = with_timeout(
+        with_request_options(request, request_options)?,
+        request_options,
+    );
+    send_with_retry(client, request.build()?, request_options, None).await
+}
+
+/// Send a request whose response body is streamed, retrying it according to [RequestOptions::retry_policy].
+///
+/// [RequestOptions::timeout] only bounds the wait for the response headers, so a long stream is not cut off.
+/// Wrap the body with [idle_timeout] to also bound the wait between two chunks.
+pub(crate) async fn send_stream(
+    client: &HttpClient,
+    request: RequestBuilder,
+    request_options: &RequestOptions,
+) -> Result<Response> {
+    let request = with_request_options(request, request_options)?;
+    send_with_retry(
+        client,
+        request.build()?,
+        request_options,
+        timeout(request_options),
+    )
+    .await
+}
+
+/// Send a request exactly once, for requests which are not safe to retry such as the chunks of an upload.
+pub(crate) async fn send_once(
+    client: &HttpClient,
+    request: RequestBuilder,
+    request_options: &RequestOptions,
+) -> Result<Response> {
+    let request = with_timeout(
+        with_request_options(request, request_options)?,
+        request_options,
+    );
+    client.transport.execute(request.build()?).await
+}
+
+/// Fail a response body stream when no chunk is received within [RequestOptions::timeout].
+pub(crate) fn idle_timeout<S, E>(
+    stream: S,
+    request_options: &RequestOptions,
+) -> impl Stream<Item = Result<Bytes>>
+where
+    S: Stream<Item = std::result::Result<Bytes, E>> + Unpin,
+    E: Into<GenerativeAIError>,
+{
+    let timeout = timeout(request_options);
+    stream::unfold(Some(stream), move |stream| async move {
+        let mut stream = stream?;
+        let next = match timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, stream.next()).await {
+                Ok(next) => next,
+                Err(_) => return Some((Err(GenerativeAIError::Timeout(timeout)), None)),
+            },
+            None => stream.next().await,
+        };
+        next.map(|chunk| (chunk.map_err(Into::into), Some(stream)))
+    })
+}
+
+/// Add the `x-goog-api-client` header and [RequestOptions::custom_headers] to a request.
+/// Custom headers replace the headers of the same name, e.g. the default `x-goog-api-client` or `content-type`.
+fn with_request_options(
+    request: RequestBuilder,
+    request_options: &RequestOptions,
+) -> Result<RequestBuilder> {
+    let api_client = match &request_options.api_client {
+        Some(api_client) => format!("{API_CLIENT} {api_client}"),
+        None => API_CLIENT.to_string(),
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.insert(API_CLIENT_HEADER, header_value(&api_client)?);
+    for (name, value) in request_options.custom_headers.iter().flatten() {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| GenerativeAIError::Validation(format!("invalid header name {name:?}")))?;
+        headers.insert(name, header_value(value)?);
+    }
+
+    // Unlike `RequestBuilder::header`, which appends, `headers` replaces the values already set.
+    Ok(request.headers(headers))
+}
+
+fn header_value(value: &str) -> Result<HeaderValue> {
+    HeaderValue::from_str(value)
+        .map_err(|_| GenerativeAIError::Validation(format!("invalid header value {value:?}")))
+}
+
+/// Bound the whole request, including reading the response body, by [RequestOptions::timeout].
+fn with_timeout(request: RequestBuilder, request_options: &RequestOptions) -> RequestBuilder {
+    match timeout(request_options) {
+        Some(timeout) => request.timeout(timeout),
+        None => request,
+    }
+}
+
+fn timeout(request_options: &RequestOptions) -> Option<Duration> {
+    request_options.timeout.map(Duration::from_millis)
+}
+
+async fn send_with_retry(
+    client: &HttpClient,
+    request: Request,
+    request_options: &RequestOptions,
+    first_byte_timeout: Option<Duration>,
+) -> Result<Response> {
+    let Some(policy) = &request_options.retry_policy else {
+        return send_attempt(client, request, first_byte_timeout).await;
+    };
+
+    let mut attempt = 1;
+    loop {
+        let Some(attempt_request) = request.try_clone() else {
+            return send_attempt(client, request, first_byte_timeout).await;
+        };
+        let last_attempt = attempt >= policy.max_attempts;
+
+        let (error, server_delay) =
+            match send_attempt(client, attempt_request, first_byte_timeout).await {
+                Ok(response)
+                    if !policy
+                        .retryable_status_codes
+                        .contains(&response.status().as_u16()) =>
+                {
+                    return Ok(response)
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let retry_after = retry_after(&response);
+                    let body = response.text().await?;
+                    let error = ApiError::from_body(status, &body);
+                    let server_delay = error.retry_delay().or(retry_after);
+                    (GenerativeAIError::Api(error), server_delay)
+                }
+                Err(err) if !last_attempt && is_transient(&err) => (err, None),
+                Err(err) => return Err(err),
+            };
+        if last_attempt {
+            return Err(error);
+        }
+
+        let Some(delay) = retry_delay(policy, attempt, server_delay) else {
+            return Err(error);
+        };
+        log::debug!("attempt {attempt} failed, retrying in {delay:?}: {error}");
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+async fn send_attempt(
+    client: &HttpClient,
+    request: Request,
+    first_byte_timeout: Option<Duration>,
+) -> Result<Response> {
+    let response = client.transport.execute(request);
+    match first_byte_timeout {
+        Some(timeout) => tokio::time::timeout(timeout, response)
+            .await
+            .map_err(|_| GenerativeAIError::Timeout(timeout))?,
+        None => response.await,
+    }
+}
+
+/// Whether an error happened before any response was received, so the request can be sent again.
+fn is_transient(err: &GenerativeAIError) -> bool {
+    match err {
+        GenerativeAIError::Transport(err) => err.is_connect() || err.is_timeout(),
+        GenerativeAIError::Timeout(_) => true,
+        _ => false,
+    }
+}
+
+/// The delay before retrying after the given failed `attempt`, starting at 1.
+///
+/// The delay asked by the server is honored as is, unless it exceeds [RetryPolicy::max_delay],
+/// in which case `None` is returned since retrying earlier would fail again.
+/// Otherwise the delay grows exponentially from [RetryPolicy::base_delay], with [RetryPolicy::jitter] applied.
+fn retry_delay(
+    policy: &RetryPolicy,
+    attempt: u32,
+    server_delay: Option<Duration>,
+) -> Option<Duration> {
+    if let Some(server_delay) = server_delay {
+        return (server_delay <= policy.max_delay).then_some(server_delay);
+    }
+
+    let exponent = attempt.saturating_sub(1).min(31);
+    let backoff = policy
+        .base_delay
+        .saturating_mul(1 << exponent)
+        .min(policy.max_delay);
+    let jitter = policy.jitter.clamp(0.0, 1.0) * (2.0 * random_fraction() - 1.0);
+
+    Some(backoff.mul_f64(1.0 + jitter).min(policy.max_delay))
+}
+
+/// The delay of a `Retry-After` header, given either in seconds or as an HTTP date.
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?;
+    parse_retry_after(value, SystemTime::now())
+}
+
+/// Parse a `Retry-After` value relative to `now`. A date in the past means no delay.
+fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = parse_http_date(value)?;
+    Some(date.duration_since(now).unwrap_or_default())
+}
+
+/// Parse an HTTP date in the IMF-fixdate format, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`.
+///
+/// The obsolete RFC 850 and asctime formats are not supported.
+fn parse_http_date(value: &str) -> Option<SystemTime> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let [_, day, month, year, time, "GMT"] = value.split_whitespace().collect::<Vec<_>>()[..]
+    else {
+        return None;
+    };
+    let day: u64 = day.parse().ok()?;
+    let month = MONTHS.iter().position(|name| *name == month)? as u64 + 1;
+    let year: u64 = year.parse().ok()?;
+    let [hour, minute, second] = time
+        .split(':')
+        .map(|field| field.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?[..]
+    else {
+        return None;
+    };
+    if year < 1970 || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    // Days since the epoch, counting years from March so that the leap day is the last day of a year,
+    // see <https://howardhinnant.github.io/date_algorithms.html#days_from_civil>.
+    let (year, month) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let year_of_era = year % 400;
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = year / 400 * 146_097 + day_of_era - 719_468;
+
+    Some(UNIX_EPOCH + Duration::from_secs(days * 86_400 + hour * 3_600 + minute * 60 + second))
+}
+
+/// A random number in `[0, 1)`. Each [RandomState] is randomly seeded, which is enough for jitter.
+fn random_fraction() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[test]
+fn test_retry_delay() {
+    let policy = RetryPolicy {
+        base_delay: Duration::from_secs(1),
+        max_delay: Duration::from_secs(10),
+        jitter: 0.0,
+        ..Default::default()
+    };
+
+    assert_eq!(retry_delay(&policy, 1, None), Some(Duration::from_secs(1)));
+    assert_eq!(retry_delay(&policy, 3, None), Some(Duration::from_secs(4)));
+    assert_eq!(
+        retry_delay(&policy, 10, None),
+        Some(Duration::from_secs(10))
+    );
+    assert_eq!(
+        retry_delay(&policy, 1, Some(Duration::from_secs(7))),
+        Some(Duration::from_secs(7))
+    );
+    assert_eq!(retry_delay(&policy, 1, Some(Duration::from_secs(60))), None);
+
+    let policy = RetryPolicy {
+        jitter: 0.5,
+        ..policy
+    };
+    for _ in 0..100 {
+        let delay = retry_delay(&policy, 2, None).unwrap();
+        assert!(delay >= Duration::from_secs(1) && delay <= Duration::from_secs(3));
+    }
+}
+
+#[test]
+fn test_retry_after() {
+    let now = UNIX_EPOCH + Duration::from_secs(784_111_777);
+
+    assert_eq!(
+        parse_retry_after(" 120 ", now),
+        Some(Duration::from_secs(120))
+    );
+    assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), Some(now));
+    assert_eq!(
+        parse_http_date("Thu, 29 Feb 2024 23:59:59 GMT"),
+        Some(UNIX_EPOCH + Duration::from_secs(1_709_251_199))
+    );
+    assert_eq!(
+        parse_retry_after("Sun, 06 Nov 1994 08:51:07 GMT", now),
+        Some(Duration::from_secs(90))
+    );
+    assert_eq!(
+        parse_retry_after("Sun, 06 Nov 1994 08:00:00 GMT", now),
+        Some(Duration::ZERO)
+    );
+    assert_eq!(
+        parse_retry_after("Sunday, 06-Nov-94 08:49:37 GMT", now),
+        None
+    );
+    assert_eq!(parse_retry_after("soon", now), None);
+}
+
+#[test]
+fn test_with_request_options() {
+    use std::collections::HashMap;
+
+    let request_options = RequestOptions {
+        api_client: Some("my-wrapper/1.2".to_string()),
+        custom_headers: Some(HashMap::from([(
+            "x-custom".to_string(),
+            "value".to_string(),
+        )])),
+        ..Default::default()
+    };
+    let request = with_request_options(
+        reqwest::Client::new().get("http://localhost"),
+        &request_options,
+    )
+    .unwrap()
+    .build()
+    .unwrap();
+    assert_eq!(
+        request.headers()["x-goog-api-client"],
+        format!("{API_CLIENT} my-wrapper/1.2")
+    );
+    assert_eq!(request.headers()["x-custom"], "value");
+
+    // Custom headers are sent once, instead of the default ones.
+    let request_options = RequestOptions {
+        custom_headers: Some(HashMap::from([
+            ("X-Goog-Api-Client".to_string(), "override".to_string()),
+            (
+                "content-type".to_string(),
+                "application/json; charset=utf-8".to_string(),
+            ),
+        ])),
+        ..Default::default()
+    };
+    let request = with_request_options(
+        reqwest::Client::new()
+            .post("http://localhost")
+            .json(&serde_json::json!({})),
+        &request_options,
+    )
+    .unwrap()
+    .build()
+    .unwrap();
+    let values = |name| {
+        request
+            .headers()
+            .get_all(name)
+            .iter()
+            .map(|value| value.to_str().unwrap())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(values("x-goog-api-client"), ["override"]);
+    assert_eq!(values("content-type"), ["application/json; charset=utf-8"]);
+
+    let request_options = RequestOptions {
+        custom_headers: Some(HashMap::from([(
+            "x-custom".to_string(),
+            "line\nbreak".to_string(),
+        )])),
+        ..Default::default()
+    };
+    let result = with_request_options(
+        reqwest::Client::new().get("http://localhost"),
+        &request_options,
+    );
+    assert!(matches!(result, Err(GenerativeAIError::Validation(_))));
+}
+
+#[tokio::test]
+async fn test_idle_timeout() {
+    let request_options = RequestOptions {
+        timeout: Some(10),
+        ..Default::default()
+    };
+    let chunks =
+        stream::iter([Ok::<_, GenerativeAIError>(Bytes::from("data"))]).chain(stream::pending());
+    let mut stream = Box::pin(idle_timeout(Box::pin(chunks), &request_options));
+
+    assert_eq!(stream.next().await.unwrap().unwrap(), "data");
+    assert!(matches!(
+        stream.next().await,
+        Some(Err(GenerativeAIError::Timeout(_)))
+    ));
+    assert!(stream.next().await.is_none());
+}
@@ -0,0 +1,289 @@
+use std::path::Path;
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use super::{
+    errors::{GenerativeAIError, Result},
+    types::{content_types::Part, requests::GenerateContentRequest},
+};
+
+/// Maximum size of inline data once base64 encoded, since the API rejects requests larger than 20 MB.
+///
+/// The helpers of this module check this limit for each part, and the inline data of a whole request are checked
+/// against it before sending, so several parts which each fit but exceed it together fail with a
+/// [GenerativeAIError::Validation]. Larger media are uploaded with the File API instead, see [FileManager::media_part_from_path][crate::v1::server::file_manager::FileManager::media_part_from_path].
+pub const MAX_INLINE_SIZE: usize = 20 * 1024 * 1024;
+
+/// Maximum number of raw bytes whose base64 encoding fits in [MAX_INLINE_SIZE].
+pub const MAX_INLINE_BYTES: usize = MAX_INLINE_SIZE / 4 * 3;
+
+/// Bytes expected at given offsets of a media.
+type Magic = &'static [(usize, &'static [u8])];
+
+/// Signatures of the supported media formats, with their MIME type. More specific signatures come first.
+const SIGNATURES: &[(Magic, &str)] = &[
+    (&[(0, b"\x89PNG\r\n\x1a\n")], "image/png"),
+    (&[(0, b"\xff\xd8\xff")], "image/jpeg"),
+    (&[(0, b"GIF87a")], "image/gif"),
+    (&[(0, b"GIF89a")], "image/gif"),
+    (&[(0, b"RIFF"), (8, b"WEBP")], "image/webp"),
+    (&[(4, b"ftypheic")], "image/heic"),
+    (&[(4, b"ftypheix")], "image/heic"),
+    (&[(4, b"ftypmif1")], "image/heif"),
+    (&[(0, b"%PDF-")], "application/pdf"),
+    (&[(0, b"RIFF"), (8, b"WAVE")], "audio/wav"),
+    (&[(0, b"FORM"), (8, b"AIFF")], "audio/aiff"),
+    (&[(0, b"ID3")], "audio/mp3"),
+    (&[(0, b"\xff\xfb")], "audio/mp3"),
+    (&[(0, b"\xff\xf3")], "audio/mp3"),
+    (&[(0, b"\xff\xf2")], "audio/mp3"),
+    (&[(0, b"\xff\xf1")], "audio/aac"),
+    (&[(0, b"\xff\xf9")], "audio/aac"),
+    (&[(0, b"OggS")], "audio/ogg"),
+    (&[(0, b"fLaC")], "audio/flac"),
+    (&[(4, b"ftypM4A ")], "audio/mp4"),
+    (&[(4, b"ftypqt  ")], "video/quicktime"),
+    (&[(4, b"ftyp3gp")], "video/3gpp"),
+    (&[(4, b"ftypisom")], "video/mp4"),
+    (&[(4, b"ftypiso2")], "video/mp4"),
+    (&[(4, b"ftypmp41")], "video/mp4"),
+    (&[(4, b"ftypmp42")], "video/mp4"),
+    (&[(4, b"ftypavc1")], "video/mp4"),
+    (&[(4, b"ftypdash")], "video/mp4"),
+    (&[(0, b"RIFF"), (8, b"AVI ")], "video/avi"),
+    (&[(0, b"\x1a\x45\xdf\xa3")], "video/webm"),
+    (&[(0, b"FLV")], "video/x-flv"),
+    (&[(0, b"\x00\x00\x01\xba")], "video/mpeg"),
+    (&[(0, b"\x30\x26\xb2\x75\x8e\x66\xcf\x11")], "video/wmv"),
+];
+
+/// MIME types of the supported media formats, by file extension.
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("heic", "image/heic"),
+    ("heif", "image/heif"),
+    ("pdf", "application/pdf"),
+    ("txt", "text/plain"),
+    ("wav", "audio/wav"),
+    ("aif", "audio/aiff"),
+    ("aiff", "audio/aiff"),
+    ("mp3", "audio/mp3"),
+    ("aac", "audio/aac"),
+    ("ogg", "audio/ogg"),
+    ("flac", "audio/flac"),
+    ("m4a", "audio/mp4"),
+    ("mov", "video/quicktime"),
+    ("3gp", "video/3gpp"),
+    ("mp4", "video/mp4"),
+    ("avi", "video/avi"),
+    ("webm", "video/webm"),
+    ("flv", "video/x-flv"),
+    ("mpg", "video/mpeg"),
+    ("mpeg", "video/mpeg"),
+    ("wmv", "video/wmv"),
+];
+
+/// Detect the MIME type of an image, audio, video or PDF from its first bytes, falling back to the extension of `path`.
+pub fn detect_mime_type(bytes: &[u8], path: Option<&Path>) -> Option<&'static str> {
+    let signature = SIGNATURES.iter().find(|(magic, _)| {
+        magic
+            .iter()
+            .all(|(offset, magic)| bytes.get(*offset..offset + magic.len()) == Some(*magic))
+    });
+    if let Some((_, mime_type)) = signature {
+        return Some(mime_type);
+    }
+
+    let extension = path?.extension()?.to_str()?;
+    EXTENSIONS
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(extension))
+        .map(|(_, mime_type)| *mime_type)
+}
+
+/// Build an inline [Part] from media bytes, base64 encoded.
+///
+/// The MIME type is detected from the bytes when not given.
+/// Fails with a [GenerativeAIError::Validation] if the media does not fit in [MAX_INLINE_SIZE].
+pub fn inline_part(bytes: &[u8], mime_type: Option<&str>) -> Result<Part> {
+    if bytes.len() > MAX_INLINE_BYTES {
+        return Err(too_large(Some(bytes.len() as u64)));
+    }
+    let mime_type = resolve_mime_type(mime_type, bytes, None)?;
+    Ok(Part::inline_data(mime_type, bytes))
+}
+
+/// Build an inline [Part] from a media file, detecting its MIME type from its content or extension.
+///
+/// Fails with a [GenerativeAIError::Validation] if the file does not fit in [MAX_INLINE_SIZE].
+pub async fn inline_part_from_path(path: impl AsRef<Path>) -> Result<Part> {
+    let path = path.as_ref();
+    let size = tokio::fs::metadata(path).await?.len();
+    if size > MAX_INLINE_BYTES as u64 {
+        return Err(too_large(Some(size)));
+    }
+
+    let bytes = tokio::fs::read(path).await?;
+    let mime_type = resolve_mime_type(None, &bytes, Some(path))?;
+    Ok(Part::inline_data(mime_type, bytes))
+}
+
+/// Build an inline [Part] from media read from an [AsyncRead].
+///
+/// The MIME type is detected from the content when not given.
+/// Fails with a [GenerativeAIError::Validation] if the media does not fit in [MAX_INLINE_SIZE],
+/// in which case no more than [MAX_INLINE_BYTES] + 1 bytes are read.
+pub async fn inline_part_from_reader<R: AsyncRead + Unpin>(
+    reader: R,
+    mime_type: Option<&str>,
+) -> Result<Part> {
+    let mut reader = reader;
+    let bytes = read_inline(&mut reader).await?;
+    if bytes.len() > MAX_INLINE_BYTES {
+        return Err(too_large(None));
+    }
+    let mime_type = resolve_mime_type(mime_type, &bytes, None)?;
+    Ok(Part::inline_data(mime_type, bytes))
+}
+
+/// Read up to [MAX_INLINE_BYTES] bytes, plus one to tell whether the media is too large to be inlined.
+pub(crate) async fn read_inline<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader
+        .take(MAX_INLINE_BYTES as u64 + 1)
+        .read_to_end(&mut bytes)
+        .await?;
+    Ok(bytes)
+}
+
+/// The given MIME type, or the detected one.
+pub(crate) fn resolve_mime_type(
+    mime_type: Option<&str>,
+    bytes: &[u8],
+    path: Option<&Path>,
+) -> Result<String> {
+    if let Some(mime_type) = mime_type {
+        return Ok(mime_type.to_string());
+    }
+    detect_mime_type(bytes, path)
+        .map(str::to_string)
+        .ok_or_else(|| {
+            GenerativeAIError::Validation(match path {
+                Some(path) => format!("cannot detect the MIME type of {}", path.display()),
+                None => "cannot detect the MIME type of the media".to_string(),
+            })
+        })
+}
+
+/// Check that the inline data of a request, all parts together, fit in [MAX_INLINE_SIZE].
+pub(crate) fn check_inline_size(request: &GenerateContentRequest) -> Result<()> {
+    let size: usize = request
+        .contents
+        .iter()
+        .chain(&request.system_instruction)
+        .flat_map(|content| &content.parts)
+        .map(|part| match part {
+            Part::InlineData { inline_data, .. } => inline_data.data.len(),
+            _ => 0,
+        })
+        .sum();
+    if size > MAX_INLINE_SIZE {
+        return Err(GenerativeAIError::Validation(format!(
+            "the inline data of the request take {size} bytes once base64 encoded, over the limit of {MAX_INLINE_SIZE} bytes, upload the larger media with the File API instead"
+        )));
+    }
+    Ok(())
+}
+
+fn too_large(size: Option<u64>) -> GenerativeAIError {
+    let media = match size {
+        Some(size) => format!("media of {size} bytes"),
+        None => "media".to_string(),
+    };
+    GenerativeAIError::Validation(format!(
+        "{media} exceeds the inline data limit of {MAX_INLINE_SIZE} bytes once base64 encoded, upload it with the File API instead"
+    ))
+}
+
+#[test]
+fn test_detect_mime_type() {
+    assert_eq!(
+        detect_mime_type(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR", None),
+        Some("image/png")
+    );
+    assert_eq!(
+        detect_mime_type(b"\xff\xd8\xff\xe0\0\x10JFIF", None),
+        Some("image/jpeg")
+    );
+    assert_eq!(
+        detect_mime_type(b"RIFF\x24\0\0\0WEBPVP8 ", None),
+        Some("image/webp")
+    );
+    assert_eq!(
+        detect_mime_type(b"RIFF\x24\0\0\0WAVEfmt ", None),
+        Some("audio/wav")
+    );
+    assert_eq!(
+        detect_mime_type(b"%PDF-1.7\n", None),
+        Some("application/pdf")
+    );
+    assert_eq!(detect_mime_type(b"ID3\x04\0\0", None), Some("audio/mp3"));
+    assert_eq!(
+        detect_mime_type(b"\0\0\0\x20ftypisom\0\0\x02\0", None),
+        Some("video/mp4")
+    );
+    assert_eq!(
+        detect_mime_type(b"\0\0\0\x18ftypmp42\0\0\0\0", None),
+        Some("video/mp4")
+    );
+    assert_eq!(
+        detect_mime_type(b"\0\0\0\x14ftypqt  \0\0\0\0", None),
+        Some("video/quicktime")
+    );
+    // Other ISO base media brands, e.g. AVIF or Canon raw images, are not taken for MP4.
+    assert_eq!(detect_mime_type(b"\0\0\0\x1cftypavif\0\0\0\0", None), None);
+    assert_eq!(detect_mime_type(b"\0\0\0\x18ftypcrx \0\0\0\0", None), None);
+
+    // The content wins over the extension, which is only a fallback.
+    let path = Path::new("notes/Report.PDF");
+    assert_eq!(
+        detect_mime_type(b"\x89PNG\r\n\x1a\n", Some(path)),
+        Some("image/png")
+    );
+    assert_eq!(
+        detect_mime_type(b"plain text", Some(path)),
+        Some("application/pdf")
+    );
+    assert_eq!(detect_mime_type(b"plain text", None), None);
+    assert_eq!(detect_mime_type(b"", Some(Path::new("archive"))), None);
+}
+
+#[tokio::test]
+async fn test_inline_part() {
+    let part = inline_part(b"%PDF-1.7\n", None).unwrap();
+    assert!(matches!(
+        &part,
+        Part::InlineData { inline_data: blob, .. } if blob.mime_type == "application/pdf" && blob.data == "JVBERi0xLjcK"
+    ));
+
+    let part = inline_part_from_reader(&b"fLaC\0\0\0\x22"[..], None)
+        .await
+        .unwrap();
+    assert!(
+        matches!(&part, Part::InlineData { inline_data: blob, .. } if blob.mime_type == "audio/flac")
+    );
+
+    let err = inline_part(b"no magic here", None).unwrap_err();
+    assert!(matches!(err, GenerativeAIError::Validation(_)));
+    assert!(inline_part(b"no magic here", Some("text/plain")).is_ok());
+
+    let too_large = tokio::io::repeat(0).take(MAX_INLINE_BYTES as u64 + 1);
+    let err = inline_part_from_reader(too_large, Some("video/mp4"))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("File API"));
+}
@@ -1,4 +1,11 @@
+pub mod auth;
+pub mod errors;
 pub mod genai;
+pub mod http;
+pub mod media;
 pub mod models;
+pub mod server;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod traits;
 pub mod types;
@@ -0,0 +1,436 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_util::{Stream, StreamExt};
+
+use crate::v1::{
+    errors::{GenerativeAIError, Result},
+    types::{
+        content_types::{Content, Role},
+        responses::{FinishReason, GenerateContentResponse},
+    },
+};
+
+use super::{generative_models::GenerativeModel, streamed_response::StreamedResponse};
+
+/// ChatSession is a multi-turn conversation with a [GenerativeModel], created by [GenerativeModel::start_chat].
+///
+/// The session keeps the history of the conversation and sends it along with every new message,
+/// so the model can answer in context.
+#[derive(Debug)]
+pub struct ChatSession<'a> {
+    model: &'a GenerativeModel,
+    history: Vec<Content>,
+}
+
+impl<'a> ChatSession<'a> {
+    pub(crate) fn new(model: &'a GenerativeModel, history: Vec<Content>) -> Self {
+        Self { model, history }
+    }
+
+    /// The conversation so far, alternating between user and model turns.
+    pub fn get_history(&self) -> &[Content] {
+        &self.history
+    }
+
+    /// Send a message to the model and get its reply.
+    ///
+    /// The user turn and the model reply are appended to the history only if the model finished its reply,
+    /// i.e. stopped naturally or reached the maximum number of tokens. Otherwise the history is left untouched
+    /// and a [GenerativeAIError::PromptBlocked] or [GenerativeAIError::IncompleteResponse] is returned,
+    /// so the message can be rephrased and sent again.
+    pub async fn send_message(
+        &mut self,
+        request: impl Into<Content>,
+    ) -> Result<GenerateContentResponse> {
+        let user_turn = request.into();
+        let mut contents = self.history.clone();
+        contents.push(user_turn.clone());
+
+        let response = self.model._generate_content(contents).await?;
+        self.commit_turn(user_turn, &response)?;
+
+        Ok(response)
+    }
+
+    /// Send a message to the model and stream its reply.
+    ///
+    /// The chunks are yielded as they arrive. The user turn and the aggregated model reply are appended to the history
+    /// only once the stream completes and the model finished its reply, as for [ChatSession::send_message].
+    /// If it fails, is blocked, or is dropped before completion, the history is left as it was before the call;
+    /// a reply which did not finish yields a last [GenerativeAIError::PromptBlocked] or [GenerativeAIError::IncompleteResponse].
+    pub async fn send_message_stream(
+        &mut self,
+        request: impl Into<Content>,
+    ) -> Result<ChatStreamedResponse<'_, 'a>> {
+        let user_turn = request.into();
+        let mut contents = self.history.clone();
+        contents.push(user_turn.clone());
+
+        let streamed = self.model._generate_content_stream(contents).await?;
+
+        Ok(ChatStreamedResponse {
+            session: self,
+            user_turn: Some(user_turn),
+            streamed,
+        })
+    }
+
+    /// Record a user turn and the model reply in `response`, unless the model did not finish its reply.
+    fn commit_turn(
+        &mut self,
+        user_turn: Content,
+        response: &GenerateContentResponse,
+    ) -> Result<()> {
+        let model_turn = model_turn(response)?;
+        self.history.push(user_turn);
+        self.history.push(model_turn);
+        Ok(())
+    }
+}
+
+/// ChatStreamedResponse is the result of [ChatSession::send_message_stream].
+///
+/// It is a [Stream] of partial [GenerateContentResponse] chunks, which commits the turn to the [ChatSession] history once exhausted,
+/// or yields a last error if the model did not finish its reply.
+#[derive(Debug)]
+pub struct ChatStreamedResponse<'s, 'a> {
+    session: &'s mut ChatSession<'a>,
+
+    /// The pending user turn, taken once the turn is committed or rolled back.
+    user_turn: Option<Content>,
+    streamed: StreamedResponse,
+}
+
+impl ChatStreamedResponse<'_, '_> {
+    /// Consume the remaining chunks, if any, and return the aggregated [GenerateContentResponse].
+    pub async fn response(mut self) -> Result<GenerateContentResponse> {
+        while let Some(chunk) = self.next().await {
+            chunk?;
+        }
+
+        match (self.streamed.is_failed(), self.streamed.aggregated()) {
+            (false, Some(aggregated)) => Ok(aggregated.clone()),
+            _ => self.streamed.response().await,
+        }
+    }
+}
+
+impl Stream for ChatStreamedResponse<'_, '_> {
+    type Item = Result<GenerateContentResponse>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let poll = this.streamed.poll_next_unpin(cx);
+
+        match &poll {
+            Poll::Ready(Some(Err(_))) => this.user_turn = None,
+            Poll::Ready(None) => {
+                if let (Some(user_turn), Some(aggregated)) =
+                    (this.user_turn.take(), this.streamed.aggregated())
+                {
+                    if let Err(err) = this.session.commit_turn(user_turn, aggregated) {
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        poll
+    }
+}
+
+/// Get the model turn to record from a response.
+///
+/// Only a candidate which finished with [FinishReason::Stop] or [FinishReason::MaxTokens] is recorded:
+/// a blocked prompt fails with [GenerativeAIError::PromptBlocked], and any other candidate with [GenerativeAIError::IncompleteResponse].
+fn model_turn(response: &GenerateContentResponse) -> Result<Content> {
+    let Some(candidate) = response.candidates.first() else {
+        return Err(match &response.prompt_feedback {
+            Some(feedback) if feedback.block_reason.is_some() => {
+                GenerativeAIError::PromptBlocked(Box::new(feedback.clone()))
+            }
+            _ => GenerativeAIError::IncompleteResponse("no candidate was returned".to_string()),
+        });
+    };
+
+    match (&candidate.finish_reason, &candidate.content) {
+        (Some(FinishReason::Stop | FinishReason::MaxTokens), Some(content))
+            if !content.parts.is_empty() =>
+        {
+            Ok(Content {
+                role: Role::Model,
+                ..content.clone()
+            })
+        }
+        (Some(FinishReason::Stop | FinishReason::MaxTokens), _) => Err(
+            GenerativeAIError::IncompleteResponse("the candidate has no content".to_string()),
+        ),
+        (finish_reason, _) => Err(GenerativeAIError::IncompleteResponse(format!(
+            "the candidate finished with {finish_reason:?}"
+        ))),
+    }
+}
+
+#[tokio::test]
+async fn test_chat_streamed_response_commits_on_completion() {
+    use crate::v1::types::model::ModelParams;
+
+    let model = GenerativeModel::new(String::new(), ModelParams::new("gemini-1.5-flash"), None);
+    let mut chat = model.start_chat(None);
+    let chunk = |text: &str| -> Result<GenerateContentResponse> {
+        Ok(serde_json::from_value(crate::v1::testing::text_response(text)).unwrap())
+    };
+    let user_turn = Content::from("Hello");
+
+    // A stream dropped before completion is rolled back.
+    let chunks = vec![chunk("Hi"), chunk(" there")];
+    let mut stream = ChatStreamedResponse {
+        session: &mut chat,
+        user_turn: Some(user_turn.clone()),
+        streamed: StreamedResponse::new(Box::pin(futures_util::stream::iter(chunks))),
+    };
+    stream.next().await.unwrap().unwrap();
+    drop(stream);
+    assert!(chat.get_history().is_empty());
+
+    // A failed stream is rolled back.
+    let chunks = vec![
+        chunk("Hi"),
+        Err(crate::v1::errors::GenerativeAIError::IncompleteResponse(
+            "connection reset".to_string(),
+        )),
+    ];
+    let stream = ChatStreamedResponse {
+        session: &mut chat,
+        user_turn: Some(user_turn.clone()),
+        streamed: StreamedResponse::new(Box::pin(futures_util::stream::iter(chunks))),
+    };
+    assert!(stream.response().await.is_err());
+    assert!(chat.get_history().is_empty());
+
+    // A stream which did not finish its reply yields a last error and is rolled back.
+    let stopped = serde_json::from_str(
+        r#"{"candidates":[{"index":0,"content":{"role":"model","parts":[{"text":" there"}]},"finishReason":"SAFETY","safetyRatings":[]}]}"#,
+    )
+    .unwrap();
+    let chunks = vec![chunk("Hi"), Ok(stopped)];
+    let mut stream = ChatStreamedResponse {
+        session: &mut chat,
+        user_turn: Some(user_turn.clone()),
+        streamed: StreamedResponse::new(Box::pin(futures_util::stream::iter(chunks))),
+    };
+    stream.next().await.unwrap().unwrap();
+    stream.next().await.unwrap().unwrap();
+    let err = stream.next().await.unwrap().unwrap_err();
+    assert!(matches!(err, GenerativeAIError::IncompleteResponse(_)));
+    assert!(stream.next().await.is_none());
+    drop(stream);
+    assert!(chat.get_history().is_empty());
+
+    // A completed stream commits the aggregated reply.
+    let chunks = vec![chunk("Hi"), chunk(" there")];
+    let stream = ChatStreamedResponse {
+        session: &mut chat,
+        user_turn: Some(user_turn),
+        streamed: StreamedResponse::new(Box::pin(futures_util::stream::iter(chunks))),
+    };
+    stream.response().await.unwrap();
+    let history = chat.get_history();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[1].parts[0].as_text(), Some("Hi there"));
+}
+
+#[test]
+fn test_chat_session_commits_only_unblocked_turns() {
+    use crate::v1::types::model::ModelParams;
+
+    let model = GenerativeModel::new(String::new(), ModelParams::new("gemini-1.5-flash"), None);
+    let mut chat = model.start_chat(None);
+    let user_turn = Content::from("Hello");
+
+    let blocked = serde_json::from_str::<GenerateContentResponse>(
+        r#"{"candidates":[],"promptFeedback":{"blockReason":"SAFETY","safetyRatings":[]},"usageMetadata":{"promptTokenCount":1,"candidatesTokenCount":0,"totalTokenCount":1}}"#,
+    )
+    .unwrap();
+    let err = chat.commit_turn(user_turn.clone(), &blocked).unwrap_err();
+    assert!(matches!(err, GenerativeAIError::PromptBlocked(_)));
+    assert!(chat.get_history().is_empty());
+
+    let stopped = serde_json::from_str::<GenerateContentResponse>(
+        r#"{"candidates":[{"index":0,"content":{"role":"model","parts":[{"text":"Hi"}]},"finishReason":"RECITATION","safetyRatings":[]}]}"#,
+    )
+    .unwrap();
+    let err = chat.commit_turn(user_turn.clone(), &stopped).unwrap_err();
+    assert!(matches!(err, GenerativeAIError::IncompleteResponse(_)));
+    assert!(chat.get_history().is_empty());
+
+    let answered = serde_json::from_str::<GenerateContentResponse>(
+        r#"{"candidates":[{"index":0,"content":{"role":"model","parts":[{"text":"Hi!"}]},"finishReason":"STOP","safetyRatings":[]}],"usageMetadata":{"promptTokenCount":1,"candidatesTokenCount":1,"totalTokenCount":2}}"#,
+    )
+    .unwrap();
+    chat.commit_turn(user_turn.clone(), &answered).unwrap();
+    let history = chat.get_history();
+    assert_eq!(history.len(), 2);
+    assert!(matches!(history[0].role, Role::User));
+    assert!(matches!(history[1].role, Role::Model));
+    assert_eq!(history[1].parts[0].as_text(), Some("Hi!"));
+
+    let truncated = serde_json::from_str::<GenerateContentResponse>(
+        r#"{"candidates":[{"index":0,"content":{"role":"model","parts":[{"text":"Well..."}]},"finishReason":"MAX_TOKENS","safetyRatings":[]}]}"#,
+    )
+    .unwrap();
+    chat.commit_turn(user_turn, &truncated).unwrap();
+    assert_eq!(chat.get_history().len(), 4);
+}
+
+#[tokio::test]
+async fn test_send_message() {
+    use reqwest::{Method, StatusCode};
+
+    use crate::v1::{
+        genai::GoogleGenerativeAI,
+        testing::{text_response, MockServer},
+        types::model::ModelParams,
+    };
+
+    let server = MockServer::start().await.unwrap();
+    let model = GoogleGenerativeAI::new("api-key").get_generative_model(
+        ModelParams::new("gemini-1.5-flash"),
+        Some(server.request_options()),
+    );
+    let mut chat = model.start_chat(None);
+    let path = "models/gemini-1.5-flash:generateContent";
+
+    server.mock_generate_content("gemini-1.5-flash", text_response("Hi!"));
+    chat.send_message("Hello").await.unwrap();
+    assert_eq!(chat.get_history().len(), 2);
+
+    // Neither a failed nor a blocked turn is committed.
+    server.mock_error(
+        Method::POST,
+        path,
+        StatusCode::BAD_REQUEST,
+        "invalid argument",
+    );
+    assert!(chat.send_message("Are you there?").await.is_err());
+    server.mock_generate_content(
+        "gemini-1.5-flash",
+        serde_json::json!({ "promptFeedback": { "blockReason": "SAFETY" } }),
+    );
+    let err = chat.send_message("Something unsafe").await.unwrap_err();
+    assert!(matches!(err, GenerativeAIError::PromptBlocked(_)));
+    server.mock_generate_content(
+        "gemini-1.5-flash",
+        serde_json::json!({
+            "candidates": [{
+                "index": 0,
+                "content": { "role": "model", "parts": [{ "text": "Sure, here is" }] },
+                "finishReason": "SAFETY",
+            }],
+        }),
+    );
+    let err = chat
+        .send_message("Something else unsafe")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, GenerativeAIError::IncompleteResponse(_)));
+    assert_eq!(chat.get_history().len(), 2);
+
+    // The next turn is sent along with the committed history.
+    server.mock_generate_content("gemini-1.5-flash", text_response("Fine, thanks."));
+    chat.send_message("How are you?").await.unwrap();
+    let requests = server.requests();
+    let contents = &requests.last().unwrap().json()["contents"];
+    assert_eq!(
+        *contents,
+        serde_json::json!([
+            { "role": "user", "parts": [{ "text": "Hello" }] },
+            { "role": "model", "parts": [{ "text": "Hi!" }] },
+            { "role": "user", "parts": [{ "text": "How are you?" }] },
+        ])
+    );
+    assert_eq!(chat.get_history().len(), 4);
+}
+
+#[tokio::test]
+async fn test_send_message_stream() {
+    use reqwest::{Method, StatusCode};
+
+    use crate::v1::{
+        genai::GoogleGenerativeAI,
+        testing::{text_response, MockServer},
+        types::model::ModelParams,
+    };
+
+    let server = MockServer::start().await.unwrap();
+    let model = GoogleGenerativeAI::new("api-key").get_generative_model(
+        ModelParams::new("gemini-1.5-flash"),
+        Some(server.request_options()),
+    );
+    let mut chat = model.start_chat(None);
+    let path = "models/gemini-1.5-flash:streamGenerateContent";
+
+    // A stream dropped before completion is rolled back.
+    server.mock_stream_generate_content(
+        "gemini-1.5-flash",
+        vec![text_response("Hi"), text_response(" there")],
+    );
+    let mut stream = chat.send_message_stream("Hello").await.unwrap();
+    stream.next().await.unwrap().unwrap();
+    drop(stream);
+    assert!(chat.get_history().is_empty());
+
+    // Neither a failed nor a blocked stream is committed.
+    server.mock_error(
+        Method::POST,
+        path,
+        StatusCode::BAD_REQUEST,
+        "invalid argument",
+    );
+    assert!(chat.send_message_stream("Hello").await.is_err());
+    server.mock_stream_generate_content(
+        "gemini-1.5-flash",
+        vec![
+            text_response("Sure"),
+            serde_json::json!({
+                "candidates": [{
+                    "index": 0,
+                    "content": { "role": "model", "parts": [{ "text": ", here is" }] },
+                    "finishReason": "SAFETY",
+                }],
+            }),
+        ],
+    );
+    let stream = chat.send_message_stream("Something unsafe").await.unwrap();
+    let err = stream.response().await.unwrap_err();
+    assert!(matches!(err, GenerativeAIError::IncompleteResponse(_)));
+    assert!(chat.get_history().is_empty());
+
+    // A completed stream commits the aggregated reply, sent along with the next turn.
+    server.mock_stream_generate_content(
+        "gemini-1.5-flash",
+        vec![text_response("Hi"), text_response(" there")],
+    );
+    let stream = chat.send_message_stream("Hello").await.unwrap();
+    stream.response().await.unwrap();
+    assert_eq!(chat.get_history().len(), 2);
+
+    server.mock_stream_generate_content("gemini-1.5-flash", vec![text_response("Fine.")]);
+    let stream = chat.send_message_stream("How are you?").await.unwrap();
+    stream.response().await.unwrap();
+    let requests = server.requests();
+    let contents = &requests.last().unwrap().json()["contents"];
+    assert_eq!(
+        *contents,
+        serde_json::json!([
+            { "role": "user", "parts": [{ "text": "Hello" }] },
+            { "role": "model", "parts": [{ "text": "Hi there" }] },
+            { "role": "user", "parts": [{ "text": "How are you?" }] },
+        ])
+    );
+    assert_eq!(chat.get_history().len(), 4);
+}
@@ -1,24 +1,43 @@
-use anyhow::Result;
-use futures_util::StreamExt;
-use reqwest::Response;
+use std::{pin::Pin, sync::Arc};
+
+use futures_util::{stream, Stream, StreamExt};
+use reqwest::{Method, Response};
 
 use crate::v1::{
-    traits::Stringify,
+    auth::ApiKey,
+    errors::{check_response, decode_body, decode_response, GenerativeAIError, Result},
+    http::{self, HttpClient},
+    media,
+    traits::{AuthProvider, Stringify},
     types::{
-        content_types::{Content, Part, Role, Tool, ToolConfig},
+        content_types::{Content, Part, Tool, ToolConfig},
         generation_types::GenerationConfig,
         model::ModelParams,
-        requests::{GenerateContentRequest, RequestOptions, Task},
-        responses::GenerateContentResponse,
+        requests::{
+            Backend, BatchEmbedContentsRequest, CountTokensInput, CountTokensRequest,
+            EmbedContentRequest, EmbedManyParams, GenerateContentRequest, RequestOptions, Task,
+            VertexCountTokensRequest, MAX_BATCH_EMBED_REQUESTS,
+        },
+        responses::{
+            BatchEmbedContentsResponse, ContentEmbedding, CountTokensResponse,
+            EmbedContentResponse, GenerateContentResponse,
+        },
         safety_types::SafetySetting,
         server::caching::CachedContent,
     },
 };
 
+use super::{chat_session::ChatSession, sse::sse_events, streamed_response::StreamedResponse};
+
+/// ResponseStream is a stream of [GenerateContentResponse] chunks returned by [GenerativeModel::generate_content_stream].
+pub type ResponseStream = Pin<Box<dyn Stream<Item = Result<GenerateContentResponse>> + Send>>;
+
 /// GenerativeModel is a model that can generate text.
 #[derive(Debug)]
 pub struct GenerativeModel {
-    api_key: String,
+    auth: Arc<dyn AuthProvider>,
+    client: HttpClient,
+    backend: Backend,
     pub model: String,
     pub request_options: RequestOptions,
     pub generation_config: Option<GenerationConfig>,
@@ -31,7 +50,16 @@ pub struct GenerativeModel {
 
 impl GenerativeModel {
     pub fn new(
-        api_key: String,
+        api_key: impl Into<ApiKey>,
+        model_params: ModelParams,
+        request_options: Option<RequestOptions>,
+    ) -> Self {
+        Self::from_auth_provider(Arc::new(api_key.into()), model_params, request_options)
+    }
+
+    /// Create a model authenticating its requests with the given provider instead of an API key.
+    pub fn from_auth_provider(
+        auth: Arc<dyn AuthProvider>,
         model_params: ModelParams,
         request_options: Option<RequestOptions>,
     ) -> Self {
@@ -42,7 +70,9 @@ impl GenerativeModel {
         };
 
         Self {
-            api_key,
+            auth,
+            client: HttpClient::default(),
+            backend: Backend::default(),
             model,
             generation_config: model_params.generation_config,
             safety_settings: model_params.safety_settings,
@@ -54,100 +84,658 @@ impl GenerativeModel {
         }
     }
 
+    /// Send the requests of this model through the given client, e.g. to share it with other models.
+    pub fn with_http_client(mut self, client: HttpClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Send the requests of this model to the given backend, e.g. [Backend::VertexAI].
+    ///
+    /// The authentication is kept as is: an [ApiKey] is only accepted by Vertex AI in express mode,
+    /// otherwise create the model with [GenerativeModel::from_auth_provider] and an OAuth2 token provider.
+    /// On Vertex AI, [GenerativeModel::count_tokens] sends the Vertex AI request shape, and embeddings are not available.
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// The resource name of the model on its backend.
+    fn _model_name(&self) -> String {
+        self.backend.model_name(&self.model)
+    }
+
     /// Create a [GenerateContentRequest][crate::v1::types::requests::GenerateContentRequest] from raw inputs
-    fn _prepare_request(&self, requests: Vec<Part>) -> GenerateContentRequest {
+    ///
+    /// When a cached content is used, only its name is sent: the system instruction, tools and tool config are
+    /// already part of the cache, and the API rejects requests setting them alongside a cached content.
+    fn _prepare_request(&self, contents: Vec<Content>) -> GenerateContentRequest {
+        let cached_content = self
+            .cached_content
+            .as_ref()
+            .and_then(|cached_content| cached_content.name.clone());
+        let uses_cache = cached_content.is_some();
+
         GenerateContentRequest {
-            model: self.model.clone(),
-            contents: vec![Content {
-                role: Role::User,
-                parts: requests,
-            }],
+            model: self._model_name(),
+            contents,
             generation_config: self.generation_config.clone(),
             safety_settings: self.safety_settings.clone(),
-            tools: self.tools.clone(),
-            tool_config: self.tool_config.clone(),
-            system_instruction: self.system_instruction.clone(),
-            cached_content: self.cached_content.clone(),
+            tools: self.tools.clone().filter(|_| !uses_cache),
+            tool_config: self.tool_config.clone().filter(|_| !uses_cache),
+            system_instruction: self.system_instruction.clone().filter(|_| !uses_cache),
+            cached_content,
         }
     }
 
-    async fn _make_model_request(
+    async fn _make_model_request<T: serde::Serialize>(
         &self,
         task: Task,
-        params: GenerateContentRequest,
+        params: T,
         stream: bool,
     ) -> Result<Response> {
-        let api_version = self
-            .request_options
-            .api_version
-            .as_ref()
-            .map(|v| v.to_str())
-            .unwrap_or_default();
-        let base_url = self.request_options.base_url.as_deref().unwrap_or_default();
-        let mut url = format!(
-            "{}/{}/{}:{}?key={}",
-            base_url,
-            api_version,
-            self.model,
-            task.to_str(),
-            self.api_key,
-        );
+        let path = format!("{}:{}", self._model_name(), task.to_str());
+        let mut url = self.backend.url(&self.request_options, &path);
         if stream {
-            url.push_str("&alt=sse");
+            url.push_str("?alt=sse");
         }
 
-        let body = serde_json::to_string(&params)?;
-        let response = reqwest::Client::new()
-            .post(&url)
-            .header("content-type", "application/json")
-            .body(body)
-            .send()
-            .await?;
+        let request = self
+            .auth
+            .authorize(
+                &self.client,
+                &self.request_options,
+                self.client.request(Method::POST, &url),
+            )
+            .await?
+            .json(&params);
 
-        Ok(response)
+        if stream {
+            http::send_stream(&self.client, request, &self.request_options).await
+        } else {
+            http::send(&self.client, request, &self.request_options).await
+        }
     }
 
     /// A multipurpose function to generate responses from the model.
     ///
     /// This `GenerativeModel.generate_content` method can handle multimodal input, and multi-turn conversations.
+    /// The request is a user turn, given as a text, a [Part] or a `Vec<Part>`.
     ///
     /// ```
     /// use google_generative_ai_rs::v1::genai::GoogleGenerativeAI;
+    /// use google_generative_ai_rs::v1::types::content_types::Part;
     /// use google_generative_ai_rs::v1::types::model::ModelParams;
     ///
     /// let api_key = "".to_string();
     /// let genai = GoogleGenerativeAI::new(api_key)
     ///     .get_generative_model(ModelParams::new("gemini-1.5-flash"), None);
-    /// let response = genai
-    ///     .generate_content(vec![google_generative_ai_rs::v1::types::content_types::Part {
-    ///         text: Some("Hello Gemini".to_string()),
-    ///         ..Default::default()
-    ///     }]);
+    /// let response = genai.generate_content("Hello Gemini");
+    /// let response = genai.generate_content(vec![
+    ///     Part::from("Summarize this report."),
+    ///     Part::file_data("application/pdf", "gs://bucket/report.pdf"),
+    /// ]);
     /// ```
-    pub async fn generate_content(&self, requests: Vec<Part>) -> Result<GenerateContentResponse> {
-        let content = self._prepare_request(requests);
+    ///
+    /// Fails with a [GenerativeAIError::Validation], without sending anything, if the inline data of the request
+    /// exceed [MAX_INLINE_SIZE][media::MAX_INLINE_SIZE] altogether.
+    pub async fn generate_content(
+        &self,
+        request: impl Into<Content>,
+    ) -> Result<GenerateContentResponse> {
+        self._generate_content(vec![request.into()]).await
+    }
+
+    /// Generate a response for a whole conversation, e.g. the history of a [ChatSession] followed by the latest user turn.
+    pub(crate) async fn _generate_content(
+        &self,
+        contents: Vec<Content>,
+    ) -> Result<GenerateContentResponse> {
+        let content = self._prepare_request(contents);
+        media::check_inline_size(&content)?;
         let response = self
             ._make_model_request(Task::GenerateContent, content, false)
             .await?;
-        let content_response = response.json().await?;
-        Ok(content_response)
+        let content_response = decode_response::<GenerateContentResponse>(response).await?;
+        check_prompt_blocked(content_response)
     }
 
-    pub async fn generate_content_stream(&self, requests: Vec<Part>) -> Result<()> {
-        let content = self._prepare_request(requests);
+    /// Generate content from the model, yielding each chunk of the response as soon as it arrives.
+    ///
+    /// The request is sent with `alt=sse` and the Server-Sent Events body is parsed incrementally,
+    /// so every item of the returned [StreamedResponse] is a partial [GenerateContentResponse].
+    /// The chunks are also merged as they arrive, see [StreamedResponse::response].
+    pub async fn generate_content_stream(
+        &self,
+        request: impl Into<Content>,
+    ) -> Result<StreamedResponse> {
+        self._generate_content_stream(vec![request.into()]).await
+    }
+
+    /// Streaming counterpart of [GenerativeModel::_generate_content].
+    pub(crate) async fn _generate_content_stream(
+        &self,
+        contents: Vec<Content>,
+    ) -> Result<StreamedResponse> {
+        let content = self._prepare_request(contents);
+        media::check_inline_size(&content)?;
         let response = self
-            ._make_model_request(Task::GenerateContent, content, true)
+            ._make_model_request(Task::StreamGenerateContent, content, true)
             .await?;
+        let response = check_response(response).await?;
+
+        let bytes = http::idle_timeout(response.bytes_stream(), &self.request_options);
+        let stream = sse_events(Box::pin(bytes)).map(|event| {
+            let chunk = decode_body::<GenerateContentResponse>(event?.data)?;
+            check_prompt_blocked(chunk)
+        });
 
-        let mut stream = response.bytes_stream();
-        while let Some(item) = stream.next().await {
-            let item = item?;
-            let str = std::str::from_utf8(&item)?;
-            for p in str.split("\n\n") {
-                dbg!(p);
+        Ok(StreamedResponse::new(Box::pin(stream)))
+    }
+
+    /// Count the number of tokens of an input, as the model would see it.
+    ///
+    /// Raw parts and contents are sent along with the model's system instruction, tools and cached content,
+    /// so the count matches what [GenerativeModel::generate_content] would be billed for.
+    /// On [Backend::VertexAI], the cached content is not counted.
+    pub async fn count_tokens(
+        &self,
+        request: impl Into<CountTokensInput>,
+    ) -> Result<CountTokensResponse> {
+        let params = self._prepare_count_tokens_request(request.into());
+        if let Some(request) = &params.generate_content_request {
+            media::check_inline_size(request)?;
+        }
+        let response = match self.backend {
+            Backend::GeminiDeveloperApi => {
+                self._make_model_request(Task::CountTokens, params, false)
+                    .await?
+            }
+            Backend::VertexAI { .. } => {
+                let params = VertexCountTokensRequest::from(params);
+                self._make_model_request(Task::CountTokens, params, false)
+                    .await?
             }
+        };
+        decode_response(response).await
+    }
+
+    /// Create a [CountTokensRequest] counting `input` against this model's parameters.
+    fn _prepare_count_tokens_request(&self, input: CountTokensInput) -> CountTokensRequest {
+        let mut generate_content_request = match input {
+            CountTokensInput::Parts(parts) => self._prepare_request(vec![parts.into()]),
+            CountTokensInput::Contents(contents) => self._prepare_request(contents),
+            CountTokensInput::GenerateContentRequest(request) => *request,
+        };
+        if generate_content_request.model.is_empty() {
+            generate_content_request.model = self._model_name();
         }
 
-        Ok(())
+        CountTokensRequest {
+            contents: None,
+            generate_content_request: Some(generate_content_request),
+        }
     }
+
+    /// Generate an embedding of the given content, for use with an embedding model such as `text-embedding-004`.
+    ///
+    /// Only available on [Backend::GeminiDeveloperApi]: Vertex AI serves embeddings through a different `:predict` endpoint.
+    pub async fn embed_content(
+        &self,
+        request: impl Into<EmbedContentRequest>,
+    ) -> Result<EmbedContentResponse> {
+        self._check_embeddings_backend()?;
+        let params = self._prepare_embed_request(request.into());
+        let response = self
+            ._make_model_request(Task::EmbedContent, params, false)
+            .await?;
+        decode_response(response).await
+    }
+
+    /// Generate embeddings for multiple contents in a single call.
+    ///
+    /// The embeddings are returned in the same order as `requests`.
+    /// Only available on [Backend::GeminiDeveloperApi], see [GenerativeModel::embed_content].
+    pub async fn batch_embed_contents(
+        &self,
+        requests: Vec<EmbedContentRequest>,
+    ) -> Result<BatchEmbedContentsResponse> {
+        self._check_embeddings_backend()?;
+        let params = BatchEmbedContentsRequest {
+            requests: requests
+                .into_iter()
+                .map(|request| self._prepare_embed_request(request))
+                .collect(),
+        };
+        let response = self
+            ._make_model_request(Task::BatchEmbedContents, params, false)
+            .await?;
+        decode_response(response).await
+    }
+
+    /// Embed an arbitrarily long sequence of texts.
+    ///
+    /// The texts are split into batches of at most [MAX_BATCH_EMBED_REQUESTS], which are sent with up to
+    /// [EmbedManyParams::concurrency] batches in flight. The embeddings are returned in the same order as `texts`,
+    /// and `on_progress` is called with the number of texts embedded so far after each batch.
+    pub async fn embed_many<I>(
+        &self,
+        texts: I,
+        params: EmbedManyParams,
+        mut on_progress: impl FnMut(usize),
+    ) -> Result<Vec<ContentEmbedding>>
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let batch_size = params.batch_size.clamp(1, MAX_BATCH_EMBED_REQUESTS);
+        let concurrency = params.concurrency.max(1);
+
+        let mut batches = stream::iter(texts)
+            .chunks(batch_size)
+            .map(|texts| {
+                let requests = texts
+                    .into_iter()
+                    .map(|text| EmbedContentRequest {
+                        task_type: params.task_type.clone(),
+                        output_dimensionality: params.output_dimensionality,
+                        ..vec![Part::from(text.into())].into()
+                    })
+                    .collect::<Vec<_>>();
+                let expected = requests.len();
+
+                async move {
+                    let response = self.batch_embed_contents(requests).await?;
+                    if response.embeddings.len() != expected {
+                        return Err(GenerativeAIError::IncompleteResponse(format!(
+                            "batchEmbedContents returned {} embeddings for {} texts",
+                            response.embeddings.len(),
+                            expected
+                        )));
+                    }
+                    Ok(response.embeddings)
+                }
+            })
+            .buffered(concurrency);
+
+        let mut embeddings = Vec::new();
+        while let Some(batch) = batches.next().await {
+            embeddings.extend(batch?);
+            on_progress(embeddings.len());
+        }
+
+        Ok(embeddings)
+    }
+
+    /// Fail with [GenerativeAIError::Validation] on backends without the `embedContent` endpoints.
+    fn _check_embeddings_backend(&self) -> Result<()> {
+        match self.backend {
+            Backend::GeminiDeveloperApi => Ok(()),
+            Backend::VertexAI { .. } => Err(GenerativeAIError::Validation(
+                "embeddings are only supported on the Gemini Developer API".to_string(),
+            )),
+        }
+    }
+
+    /// Fill in the model of an [EmbedContentRequest] when left empty.
+    fn _prepare_embed_request(&self, mut request: EmbedContentRequest) -> EmbedContentRequest {
+        if request.model.is_empty() {
+            request.model = self._model_name();
+        }
+        request
+    }
+
+    /// Start a multi-turn [ChatSession] with this model, optionally seeded with a previous `history`.
+    pub fn start_chat(&self, history: Option<Vec<Content>>) -> ChatSession<'_> {
+        ChatSession::new(self, history.unwrap_or_default())
+    }
+}
+
+/// Fail with [GenerativeAIError::PromptBlocked] when the prompt was blocked and no candidates were returned.
+fn check_prompt_blocked(response: GenerateContentResponse) -> Result<GenerateContentResponse> {
+    match response.prompt_feedback {
+        Some(feedback) if feedback.block_reason.is_some() && response.candidates.is_empty() => {
+            Err(GenerativeAIError::PromptBlocked(Box::new(feedback)))
+        }
+        _ => Ok(response),
+    }
+}
+
+#[test]
+fn test_prepare_count_tokens_request() {
+    let model = GenerativeModel::new(
+        String::new(),
+        ModelParams {
+            system_instruction: Some(Content::from("Be concise.")),
+            ..ModelParams::new("gemini-1.5-flash")
+        },
+        None,
+    );
+    let parts = vec![Part::from("Hello")];
+
+    let request = model._prepare_count_tokens_request(parts.into());
+    let request = serde_json::to_value(request).unwrap();
+    assert!(request.get("contents").is_none());
+    let generate_content_request = &request["generateContentRequest"];
+    assert_eq!(generate_content_request["model"], "models/gemini-1.5-flash");
+    assert_eq!(
+        generate_content_request["contents"][0]["parts"][0]["text"],
+        "Hello"
+    );
+    assert_eq!(
+        generate_content_request["systemInstruction"]["parts"][0]["text"],
+        "Be concise."
+    );
+
+    let request = model._prepare_count_tokens_request(GenerateContentRequest::default().into());
+    assert_eq!(
+        request.generate_content_request.unwrap().model,
+        "models/gemini-1.5-flash"
+    );
+}
+
+#[test]
+fn test_prepare_embed_request() {
+    use crate::v1::types::requests::TaskType;
+
+    let model = GenerativeModel::new(String::new(), ModelParams::new("text-embedding-004"), None);
+    let request = EmbedContentRequest {
+        task_type: Some(TaskType::RetrievalDocument),
+        title: Some("Rust".to_string()),
+        output_dimensionality: Some(256),
+        ..vec![Part::from("Rust is a systems programming language.")].into()
+    };
+
+    let request = serde_json::to_value(model._prepare_embed_request(request)).unwrap();
+    assert_eq!(
+        request,
+        serde_json::json!({
+            "model": "models/text-embedding-004",
+            "content": {
+                "role": "user",
+                "parts": [{ "text": "Rust is a systems programming language." }],
+            },
+            "taskType": "RETRIEVAL_DOCUMENT",
+            "title": "Rust",
+            "outputDimensionality": 256,
+        })
+    );
+}
+
+#[test]
+fn test_prepare_request_with_cached_content() {
+    let system_instruction = Content::from("You are an expert on the attached documents.");
+    let cached_content = CachedContent {
+        name: Some("cachedContents/abc123".to_string()),
+        model: "models/gemini-1.5-flash-001".to_string(),
+        system_instruction: Some(system_instruction),
+        tools: Some(vec![Tool {
+            function_declarations: None,
+            code_execution: Some(Default::default()),
+        }]),
+        tool_config: Some(ToolConfig {
+            function_calling_config: crate::v1::types::content_types::FunctionCalingConfig {
+                mode: Some(crate::v1::types::content_types::FunctionCallingMode::None),
+                allowed_function_names: None,
+            },
+        }),
+        ..Default::default()
+    };
+
+    let model = crate::v1::genai::GoogleGenerativeAI::new(String::new())
+        .get_generative_model_from_cached_content(&cached_content, None, None)
+        .unwrap();
+    assert_eq!(model.model, "models/gemini-1.5-flash-001");
+    assert!(model.system_instruction.is_some());
+    assert!(model.tools.is_some());
+    assert!(model.tool_config.is_some());
+
+    // The cached content already holds them, so they are not sent again.
+    let request = serde_json::to_value(model._prepare_request(Vec::new())).unwrap();
+    assert_eq!(request["cachedContent"], "cachedContents/abc123");
+    assert!(request.get("systemInstruction").is_none());
+    assert!(request.get("tools").is_none());
+    assert!(request.get("toolConfig").is_none());
+
+    let model = GenerativeModel::new(
+        String::new(),
+        ModelParams {
+            cached_content: Some("abc123".into()),
+            ..ModelParams::new("gemini-1.5-flash-001")
+        },
+        None,
+    );
+    let request = serde_json::to_value(model._prepare_request(Vec::new())).unwrap();
+    assert_eq!(request["cachedContent"], "cachedContents/abc123");
+}
+
+#[test]
+fn test_vertex_ai_request() {
+    let request_options = RequestOptions::default();
+    let model = GenerativeModel::new(
+        String::new(),
+        ModelParams::new("gemini-1.5-flash"),
+        Some(RequestOptions {
+            api_version: Some(crate::v1::types::requests::ApiVersion::V1),
+            ..Default::default()
+        }),
+    )
+    .with_backend(Backend::vertex_ai("my-project", "us-central1"));
+
+    let name =
+        "projects/my-project/locations/us-central1/publishers/google/models/gemini-1.5-flash";
+    assert_eq!(model._model_name(), name);
+    assert_eq!(
+        model
+            .backend
+            .url(&model.request_options, &format!("{name}:generateContent")),
+        format!("https://us-central1-aiplatform.googleapis.com/v1/{name}:generateContent")
+    );
+    assert_eq!(
+        model
+            .backend
+            .url(&request_options, model.backend.models_path()),
+        "https://us-central1-aiplatform.googleapis.com/v1beta1/publishers/google/models"
+    );
+
+    let request =
+        model._prepare_request(vec![
+            Part::file_data("image/png", "gs://bucket/image.png").into()
+        ]);
+    assert_eq!(request.model, name);
+    assert!(matches!(
+        &request.contents[0].parts[0],
+        Part::FileData { file_data, .. } if file_data.uri == "gs://bucket/image.png"
+    ));
+
+    assert_eq!(
+        Backend::GeminiDeveloperApi
+            .url(&request_options, "models/gemini-1.5-flash:generateContent"),
+        "https://generativelanguage.googleapis.com/v1beta/models/gemini-1.5-flash:generateContent"
+    );
+}
+
+#[tokio::test]
+async fn test_embed_many() {
+    use crate::v1::{
+        genai::GoogleGenerativeAI,
+        testing::{MockResponse, MockServer},
+    };
+
+    // The `i`th batch answered gets embeddings `[i, j]`, `j` being the position in the batch.
+    let start = |sizes: &[usize]| {
+        let sizes = sizes.to_vec();
+        async move {
+            let server = MockServer::start().await.unwrap();
+            for (i, size) in sizes.into_iter().enumerate() {
+                let embeddings: Vec<_> = (0..size)
+                    .map(|j| serde_json::json!({ "values": [i, j] }))
+                    .collect();
+                server.mock(
+                    Method::POST,
+                    "models/text-embedding-004:batchEmbedContents",
+                    MockResponse::json(serde_json::json!({ "embeddings": embeddings })),
+                );
+            }
+            let model = GoogleGenerativeAI::new("api-key").get_generative_model(
+                ModelParams::new("text-embedding-004"),
+                Some(server.request_options()),
+            );
+            (server, model)
+        }
+    };
+    let texts: Vec<String> = (0..250).map(|k| k.to_string()).collect();
+
+    // More than MAX_BATCH_EMBED_REQUESTS texts are split into batches.
+    let (server, model) = start(&[100, 100, 50]).await;
+    let mut progress = Vec::new();
+    let params = EmbedManyParams {
+        concurrency: 1,
+        ..Default::default()
+    };
+    let embeddings = model
+        .embed_many(texts.clone(), params, |done| progress.push(done))
+        .await
+        .unwrap();
+    assert_eq!(embeddings.len(), 250);
+    assert_eq!(progress, [100, 200, 250]);
+    let batches: Vec<usize> = server
+        .requests()
+        .iter()
+        .map(|request| request.json()["requests"].as_array().unwrap().len())
+        .collect();
+    assert_eq!(batches, [100, 100, 50]);
+
+    // Concurrent batches may be answered in any order, yet the embeddings follow the texts.
+    let (server, model) = start(&[50; 5]).await;
+    let params = EmbedManyParams {
+        batch_size: 50,
+        concurrency: 5,
+        ..Default::default()
+    };
+    let mut progress = Vec::new();
+    let embeddings = model
+        .embed_many(texts.clone(), params, |done| progress.push(done))
+        .await
+        .unwrap();
+    assert_eq!(progress, [50, 100, 150, 200, 250]);
+    // Requests are recorded in the order they took the fixtures.
+    for (i, request) in server.requests().iter().enumerate() {
+        let requests = request.json()["requests"].clone();
+        for (j, request) in requests.as_array().unwrap().iter().enumerate() {
+            let text = request["content"]["parts"][0]["text"].as_str().unwrap();
+            let k: usize = text.parse().unwrap();
+            assert_eq!(embeddings[k].values, [i as f32, j as f32]);
+        }
+    }
+
+    // A batch answered with the wrong number of embeddings fails.
+    let (_server, model) = start(&[100, 99]).await;
+    let err = model
+        .embed_many(
+            texts.into_iter().take(200),
+            EmbedManyParams::default(),
+            |_| {},
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, GenerativeAIError::IncompleteResponse(_)));
+}
+
+#[tokio::test]
+async fn test_vertex_ai_endpoints() {
+    use crate::v1::{
+        auth::BearerToken,
+        testing::{text_response, MockResponse, MockServer},
+    };
+
+    let server = MockServer::start().await.unwrap();
+    let model = GenerativeModel::from_auth_provider(
+        Arc::new(BearerToken::new("token")),
+        ModelParams {
+            system_instruction: Some(Content::from("Be concise.")),
+            ..ModelParams::new("gemini-1.5-flash")
+        },
+        Some(server.request_options()),
+    )
+    .with_backend(Backend::vertex_ai("my-project", "us-central1"));
+    let name =
+        "projects/my-project/locations/us-central1/publishers/google/models/gemini-1.5-flash";
+
+    server.mock(
+        Method::POST,
+        &format!("{name}:generateContent"),
+        MockResponse::json(text_response("Hi!")),
+    );
+    model.generate_content("Hello").await.unwrap();
+    let request = server.requests().pop().unwrap();
+    assert_eq!(request.path, format!("/v1beta1/{name}:generateContent"));
+    assert_eq!(request.header("authorization"), Some("Bearer token"));
+    assert_eq!(request.json()["contents"][0]["parts"][0]["text"], "Hello");
+
+    // countTokens takes the request fields at the top level on Vertex AI.
+    server.mock(
+        Method::POST,
+        &format!("{name}:countTokens"),
+        MockResponse::json(serde_json::json!({ "totalTokens": 5 })),
+    );
+    let response = model.count_tokens(vec![Part::from("Hello")]).await.unwrap();
+    assert_eq!(response.total_tokens, 5);
+    let request = server.requests().pop().unwrap();
+    assert_eq!(request.path, format!("/v1beta1/{name}:countTokens"));
+    assert_eq!(
+        request.json(),
+        serde_json::json!({
+            "contents": [{ "role": "user", "parts": [{ "text": "Hello" }] }],
+            "systemInstruction": { "role": "user", "parts": [{ "text": "Be concise." }] },
+        })
+    );
+
+    let err = model
+        .embed_content(vec![Part::from("Hello")])
+        .await
+        .unwrap_err();
+    assert!(matches!(err, GenerativeAIError::Validation(_)));
+    let err = model
+        .embed_many(["Hello"], EmbedManyParams::default(), |_| {})
+        .await
+        .unwrap_err();
+    assert!(matches!(err, GenerativeAIError::Validation(_)));
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_inline_data_limit() {
+    use crate::v1::{
+        genai::GoogleGenerativeAI, media::MAX_INLINE_SIZE, testing::MockServer,
+        types::content_types::GenerativeContentBlob,
+    };
+
+    let server = MockServer::start().await.unwrap();
+    let model = GoogleGenerativeAI::new("api-key").get_generative_model(
+        ModelParams::new("gemini-1.5-flash"),
+        Some(server.request_options()),
+    );
+
+    // Each part fits on its own, but not both together.
+    let half = || {
+        Part::from(GenerativeContentBlob {
+            mime_type: "video/mp4".to_string(),
+            data: "A".repeat(MAX_INLINE_SIZE / 2 + 4),
+            extra: Default::default(),
+        })
+    };
+    let err = model
+        .generate_content(vec![half(), half()])
+        .await
+        .unwrap_err();
+    assert!(matches!(err, GenerativeAIError::Validation(_)));
+    let err = model
+        .generate_content_stream(vec![half(), half()])
+        .await
+        .unwrap_err();
+    assert!(matches!(err, GenerativeAIError::Validation(_)));
+    let err = model.count_tokens(vec![half(), half()]).await.unwrap_err();
+    assert!(matches!(err, GenerativeAIError::Validation(_)));
+    assert!(server.requests().is_empty());
 }
@@ -1,35 +1,53 @@
+use reqwest::Method;
+
 use super::{
-    traits::Stringify,
-    types::{model::Model, requests::RequestOptions, responses::ListModelResponse},
+    errors::{decode_response, Result},
+    http::{self, HttpClient},
+    traits::AuthProvider,
+    types::{
+        model::Model,
+        requests::{Backend, RequestOptions},
+        responses::ListModelResponse,
+    },
 };
 
+pub mod chat_session;
 pub mod generative_models;
+mod sse;
+pub mod streamed_response;
 
 pub async fn get_model_list(
-    api_key: String,
+    client: &HttpClient,
+    auth: &dyn AuthProvider,
+    backend: &Backend,
     request_options: Option<RequestOptions>,
-) -> Result<ListModelResponse, reqwest::Error> {
+) -> Result<ListModelResponse> {
     let request_options = request_options.unwrap_or_default();
-    let api_version = request_options.api_version.unwrap_or_default().to_str();
-    let base_url = request_options.base_url.unwrap_or_default();
-
-    let url = format!("{base_url}/{api_version}/models?key={api_key}");
-    let response = reqwest::Client::new().get(url).send().await?;
+    let url = backend.url(&request_options, backend.models_path());
+    let request = auth
+        .authorize(client, &request_options, client.request(Method::GET, url))
+        .await?;
+    let response = http::send(client, request, &request_options).await?;
 
-    response.json::<ListModelResponse>().await
+    decode_response(response).await
 }
 
 pub async fn get_model_info(
-    api_key: String,
+    client: &HttpClient,
+    auth: &dyn AuthProvider,
+    backend: &Backend,
     model: String,
     request_options: Option<RequestOptions>,
-) -> Result<Model, reqwest::Error> {
+) -> Result<Model> {
     let request_options = request_options.unwrap_or_default();
-    let api_version = request_options.api_version.unwrap_or_default().to_str();
-    let base_url = request_options.base_url.unwrap_or_default();
-
-    let url = format!("{base_url}/{api_version}/models/{model}?key={api_key}");
-    let response = reqwest::Client::new().get(url).send().await?;
+    let url = backend.url(
+        &request_options,
+        &format!("{}/{model}", backend.models_path()),
+    );
+    let request = auth
+        .authorize(client, &request_options, client.request(Method::GET, url))
+        .await?;
+    let response = http::send(client, request, &request_options).await?;
 
-    Ok(response.json::<Model>().await.unwrap())
+    decode_response(response).await
 }
@@ -0,0 +1,226 @@
+use std::collections::VecDeque;
+
+use bytes::Bytes;
+use futures_util::{stream, Stream, StreamExt};
+
+use crate::v1::errors::{GenerativeAIError, Result};
+
+/// SseEvent is a single event dispatched by [SseParser].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct SseEvent {
+    /// The event type, set by an `event:` field. `None` means the default `message` type.
+    pub event: Option<String>,
+
+    /// The concatenated `data:` fields of the event, joined with `\n`.
+    pub data: String,
+
+    /// The last event ID, set by an `id:` field.
+    pub id: Option<String>,
+}
+
+/// SseParser is an incremental parser for the `text/event-stream` format.
+///
+/// Bytes may be fed in arbitrary chunks: lines and events split across chunk boundaries are buffered until complete.
+/// Lines may end with `\r\n`, `\n` or `\r`, and comment lines (starting with `:`) are ignored.
+/// See the [HTML specification](https://html.spec.whatwg.org/multipage/server-sent-events.html#event-stream-interpretation).
+#[derive(Debug, Default)]
+pub(crate) struct SseParser {
+    /// Bytes of the current, not yet terminated, line.
+    buffer: Vec<u8>,
+
+    /// The previous chunk ended with `\r`, so a leading `\n` in the next chunk belongs to the same line ending.
+    skip_lf: bool,
+
+    event: Option<String>,
+    data: String,
+    id: Option<String>,
+}
+
+impl SseParser {
+    /// Feed a chunk of bytes into the parser, returning every event completed by it.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        let mut events = Vec::new();
+        let mut chunk = chunk;
+
+        if self.skip_lf {
+            self.skip_lf = false;
+            if let Some(rest) = chunk.strip_prefix(b"\n") {
+                chunk = rest;
+            }
+        }
+
+        while let Some(pos) = chunk.iter().position(|b| *b == b'\r' || *b == b'\n') {
+            self.buffer.extend_from_slice(&chunk[..pos]);
+            let line = std::mem::take(&mut self.buffer);
+            if let Some(event) = self.process_line(&line) {
+                events.push(event);
+            }
+
+            let consumed = match (chunk[pos], chunk.get(pos + 1)) {
+                (b'\r', Some(b'\n')) => 2,
+                (b'\r', None) => {
+                    self.skip_lf = true;
+                    1
+                }
+                _ => 1,
+            };
+            chunk = &chunk[pos + consumed..];
+        }
+        self.buffer.extend_from_slice(chunk);
+
+        events
+    }
+
+    /// Flush the parser at the end of the stream.
+    ///
+    /// Unlike the specification, which discards an event that is not terminated by a blank line,
+    /// this dispatches it so a truncated final event is not silently lost.
+    pub fn finish(&mut self) -> Option<SseEvent> {
+        if !self.buffer.is_empty() {
+            let line = std::mem::take(&mut self.buffer);
+            if let Some(event) = self.process_line(&line) {
+                return Some(event);
+            }
+        }
+        self.dispatch()
+    }
+
+    fn process_line(&mut self, line: &[u8]) -> Option<SseEvent> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(b":") {
+            return None;
+        }
+
+        let line = String::from_utf8_lossy(line);
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line.as_ref(), ""),
+        };
+
+        match field {
+            "data" => {
+                self.data.push_str(value);
+                self.data.push('\n');
+            }
+            "event" => self.event = Some(value.to_string()),
+            "id" if !value.contains('\0') => self.id = Some(value.to_string()),
+            _ => {}
+        }
+
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let event = self.event.take();
+        if self.data.is_empty() {
+            return None;
+        }
+
+        let mut data = std::mem::take(&mut self.data);
+        data.pop();
+
+        Some(SseEvent {
+            event,
+            data,
+            id: self.id.clone(),
+        })
+    }
+}
+
+/// Turn a stream of bytes, such as [reqwest::Response::bytes_stream], into a stream of [SseEvent].
+pub(crate) fn sse_events<S, E>(bytes: S) -> impl Stream<Item = Result<SseEvent>>
+where
+    S: Stream<Item = std::result::Result<Bytes, E>> + Unpin,
+    E: Into<GenerativeAIError>,
+{
+    let state = (bytes, SseParser::default(), VecDeque::new(), false);
+
+    stream::unfold(
+        state,
+        |(mut bytes, mut parser, mut pending, mut done)| async move {
+            loop {
+                if let Some(event) = pending.pop_front() {
+                    return Some((Ok(event), (bytes, parser, pending, done)));
+                }
+                if done {
+                    return None;
+                }
+
+                match bytes.next().await {
+                    Some(Ok(chunk)) => pending.extend(parser.feed(&chunk)),
+                    Some(Err(err)) => {
+                        done = true;
+                        return Some((Err(err.into()), (bytes, parser, pending, done)));
+                    }
+                    None => {
+                        done = true;
+                        pending.extend(parser.finish());
+                    }
+                }
+            }
+        },
+    )
+}
+
+#[test]
+fn test_sse_parser_split_chunks() {
+    let mut parser = SseParser::default();
+
+    assert!(parser.feed(b"data: {\"a\":").is_empty());
+    assert!(parser.feed(b"1}\r").is_empty());
+    let mut events = parser.feed(b"\n\r");
+    events.extend(parser.feed(b"\ndata: second\n\n"));
+
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].data, "{\"a\":1}");
+    assert_eq!(events[1].data, "second");
+    assert_eq!(parser.finish(), None);
+}
+
+#[test]
+fn test_sse_parser_multiline_data_and_comments() {
+    let mut parser = SseParser::default();
+
+    let events = parser.feed(
+        b": keep-alive\r\nevent: update\r\nid: 7\r\ndata: line one\r\ndata:line two\r\ndata\r\n\r\n",
+    );
+
+    assert_eq!(
+        events,
+        vec![SseEvent {
+            event: Some("update".to_string()),
+            data: "line one\nline two\n".to_string(),
+            id: Some("7".to_string()),
+        }]
+    );
+}
+
+#[test]
+fn test_sse_parser_ignores_empty_events_and_flushes_trailing_data() {
+    let mut parser = SseParser::default();
+
+    assert!(parser.feed(b"event: ping\n\n:comment only\n\n").is_empty());
+    assert!(parser.feed(b"data: last").is_empty());
+
+    let event = parser.finish().unwrap();
+    assert_eq!(event.event, None);
+    assert_eq!(event.data, "last");
+}
+
+#[tokio::test]
+async fn test_sse_events_stream() {
+    let chunks: Vec<std::result::Result<Bytes, std::io::Error>> = vec![
+        Ok(Bytes::from_static(b"data: one\r")),
+        Ok(Bytes::from_static(b"\n\r\nda")),
+        Ok(Bytes::from_static(b"ta: two\n\n")),
+    ];
+
+    let events = sse_events(stream::iter(chunks))
+        .map(|event| event.unwrap().data)
+        .collect::<Vec<_>>()
+        .await;
+
+    assert_eq!(events, vec!["one", "two"]);
+}
@@ -0,0 +1,289 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_util::{Stream, StreamExt};
+
+use crate::v1::{
+    errors::{GenerativeAIError, Result},
+    types::{
+        content_types::Part,
+        responses::{Candidate, GenerateContentResponse},
+    },
+};
+
+use super::generative_models::ResponseStream;
+
+/// StreamedResponse is the result of [generate_content_stream][crate::v1::models::generative_models::GenerativeModel::generate_content_stream].
+///
+/// It is a [Stream] of partial [GenerateContentResponse] chunks which, while being consumed, merges every chunk into a single aggregated response.
+/// The aggregated response is available through [StreamedResponse::response], either after iterating over the chunks or instead of it.
+pub struct StreamedResponse {
+    stream: ResponseStream,
+    aggregated: Option<GenerateContentResponse>,
+    error: Option<String>,
+    done: bool,
+}
+
+impl StreamedResponse {
+    pub(crate) fn new(stream: ResponseStream) -> Self {
+        Self {
+            stream,
+            aggregated: None,
+            error: None,
+            done: false,
+        }
+    }
+
+    /// The response aggregated so far, or `None` if no chunk was received yet.
+    pub(crate) fn aggregated(&self) -> Option<&GenerateContentResponse> {
+        self.aggregated.as_ref()
+    }
+
+    /// Whether the stream yielded an error.
+    pub(crate) fn is_failed(&self) -> bool {
+        self.error.is_some()
+    }
+
+    /// Consume the remaining chunks, if any, and return the aggregated [GenerateContentResponse].
+    ///
+    /// Fails if any chunk failed, or if the stream ended without yielding a single chunk.
+    pub async fn response(mut self) -> Result<GenerateContentResponse> {
+        while let Some(chunk) = self.next().await {
+            chunk?;
+        }
+
+        if let Some(error) = self.error {
+            return Err(GenerativeAIError::IncompleteResponse(format!(
+                "stream ended with an error: {error}"
+            )));
+        }
+        self.aggregated.ok_or_else(|| {
+            GenerativeAIError::IncompleteResponse("stream ended without any response".to_string())
+        })
+    }
+}
+
+impl std::fmt::Debug for StreamedResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamedResponse")
+            .field("aggregated", &self.aggregated)
+            .field("error", &self.error)
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Stream for StreamedResponse {
+    type Item = Result<GenerateContentResponse>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+        let poll = self.stream.as_mut().poll_next(cx);
+
+        match &poll {
+            Poll::Ready(Some(Ok(chunk))) => match self.aggregated.as_mut() {
+                Some(aggregated) => aggregate_response(aggregated, chunk.clone()),
+                None => self.aggregated = Some(chunk.clone()),
+            },
+            Poll::Ready(Some(Err(err))) => self.error = Some(err.to_string()),
+            Poll::Ready(None) => self.done = true,
+            Poll::Pending => {}
+        }
+
+        poll
+    }
+}
+
+/// Merge a streamed `chunk` into the `aggregated` response.
+///
+/// Consecutive text parts, and thought parts, of a candidate are concatenated, other parts such as function calls are collected in order,
+/// and the latest `finish_reason`, `prompt_feedback` and `usage_metadata` win.
+fn aggregate_response(aggregated: &mut GenerateContentResponse, chunk: GenerateContentResponse) {
+    for candidate in chunk.candidates {
+        match aggregated
+            .candidates
+            .iter_mut()
+            .find(|c| c.index == candidate.index)
+        {
+            Some(existing) => aggregate_candidate(existing, candidate),
+            None => aggregated.candidates.push(candidate),
+        }
+    }
+
+    if chunk.prompt_feedback.is_some() {
+        aggregated.prompt_feedback = chunk.prompt_feedback;
+    }
+    if chunk.usage_metadata.is_some() {
+        aggregated.usage_metadata = chunk.usage_metadata;
+    }
+}
+
+fn aggregate_candidate(aggregated: &mut Candidate, chunk: Candidate) {
+    if let Some(content) = chunk.content {
+        match aggregated.content.as_mut() {
+            Some(existing) => aggregate_parts(&mut existing.parts, content.parts),
+            None => aggregated.content = Some(content),
+        }
+    }
+
+    if chunk.finish_reason.is_some() {
+        aggregated.finish_reason = chunk.finish_reason;
+    }
+    if chunk.token_count.is_some() {
+        aggregated.token_count = chunk.token_count;
+    }
+
+    for rating in chunk.safety_ratings {
+        match aggregated
+            .safety_ratings
+            .iter_mut()
+            .find(|r| r.category == rating.category)
+        {
+            Some(existing) => *existing = rating,
+            None => aggregated.safety_ratings.push(rating),
+        }
+    }
+
+    if let Some(citation_metadata) = chunk.citation_metadata {
+        match aggregated.citation_metadata.as_mut() {
+            Some(existing) => existing
+                .citation_sources
+                .extend(citation_metadata.citation_sources),
+            None => aggregated.citation_metadata = Some(citation_metadata),
+        }
+    }
+}
+
+/// Consecutive text parts, and consecutive thoughts, are concatenated along with their unknown fields.
+fn aggregate_parts(aggregated: &mut Vec<Part>, parts: Vec<Part>) {
+    for part in parts {
+        match (aggregated.last_mut(), part) {
+            (
+                Some(Part::Text { text, extra }),
+                Part::Text {
+                    text: more,
+                    extra: more_extra,
+                },
+            )
+            | (
+                Some(Part::Thought { text, extra }),
+                Part::Thought {
+                    text: more,
+                    extra: more_extra,
+                },
+            ) => {
+                text.push_str(&more);
+                extra.extend(more_extra);
+            }
+            (_, part) => aggregated.push(part),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_streamed_response_aggregates_chunks() {
+    use crate::v1::types::responses::FinishReason;
+
+    let chunks = [
+        r#"{"candidates":[{"index":0,"content":{"role":"model","parts":[{"text":"Hello"}]},"safetyRatings":[{"category":"HARM_CATEGORY_HARASSMENT","probability":"NEGLIGIBLE"}]}],"usageMetadata":{"promptTokenCount":3,"candidatesTokenCount":1,"totalTokenCount":4}}"#,
+        r#"{"candidates":[{"index":0,"content":{"role":"model","parts":[{"text":", world"},{"functionCall":{"name":"lookup","args":{}}}]},"safetyRatings":[{"category":"HARM_CATEGORY_HARASSMENT","probability":"LOW"}],"citationMetadata":{"citationSources":[{"uri":"https://example.com"}]}}],"usageMetadata":{"promptTokenCount":3,"candidatesTokenCount":2,"totalTokenCount":5}}"#,
+        r#"{"candidates":[{"index":0,"content":{"role":"model","parts":[{"text":"!"}]},"finishReason":"STOP","safetyRatings":[]}],"usageMetadata":{"promptTokenCount":3,"candidatesTokenCount":4,"totalTokenCount":7}}"#,
+    ];
+    let chunks = chunks
+        .into_iter()
+        .map(|chunk| Ok(serde_json::from_str::<GenerateContentResponse>(chunk).unwrap()))
+        .collect::<Vec<_>>();
+
+    let mut streamed = StreamedResponse::new(Box::pin(futures_util::stream::iter(chunks)));
+    assert!(streamed.next().await.unwrap().is_ok());
+    let response = streamed.response().await.unwrap();
+
+    let candidate = &response.candidates[0];
+    let parts = &candidate.content.as_ref().unwrap().parts;
+    assert_eq!(parts.len(), 3);
+    assert_eq!(parts[0].as_text(), Some("Hello, world"));
+    assert_eq!(parts[1].as_function_call().unwrap().name, "lookup");
+    assert_eq!(parts[2].as_text(), Some("!"));
+    assert!(matches!(candidate.finish_reason, Some(FinishReason::Stop)));
+    assert_eq!(candidate.safety_ratings.len(), 1);
+    assert_eq!(
+        candidate
+            .citation_metadata
+            .as_ref()
+            .unwrap()
+            .citation_sources
+            .len(),
+        1
+    );
+    assert_eq!(response.usage_metadata.unwrap().total_token_count, 7);
+}
+
+#[tokio::test]
+async fn test_streamed_response_partial_chunks() {
+    use crate::v1::types::responses::FinishReason;
+
+    let chunks = [
+        r#"{"candidates":[{"content":{"role":"model","parts":[{"text":"Once upon"}]}}],"usageMetadata":{"promptTokenCount":3,"candidatesTokenCount":2,"totalTokenCount":5}}"#,
+        r#"{}"#,
+        r#"{"candidates":[{"finishReason":"SAFETY","safetyRatings":[{"category":"HARM_CATEGORY_DANGEROUS_CONTENT","probability":"HIGH","blocked":true}]}]}"#,
+    ];
+    let chunks = chunks
+        .into_iter()
+        .map(|chunk| Ok(serde_json::from_str::<GenerateContentResponse>(chunk).unwrap()))
+        .collect::<Vec<_>>();
+
+    let streamed = StreamedResponse::new(Box::pin(futures_util::stream::iter(chunks)));
+    let response = streamed.response().await.unwrap();
+
+    let candidate = &response.candidates[0];
+    assert_eq!(
+        candidate.content.as_ref().unwrap().parts[0].as_text(),
+        Some("Once upon")
+    );
+    assert!(matches!(
+        candidate.finish_reason,
+        Some(FinishReason::Safety)
+    ));
+    assert_eq!(candidate.safety_ratings.len(), 1);
+    assert_eq!(response.usage_metadata.unwrap().total_token_count, 5);
+}
+
+#[tokio::test]
+async fn test_streamed_response_reports_errors() {
+    let chunks: Vec<Result<GenerateContentResponse>> = vec![Err(
+        GenerativeAIError::IncompleteResponse("connection reset".to_string()),
+    )];
+
+    let streamed = StreamedResponse::new(Box::pin(futures_util::stream::iter(chunks)));
+    let err = streamed.response().await.unwrap_err();
+    assert!(err.to_string().contains("connection reset"));
+
+    let empty = StreamedResponse::new(Box::pin(futures_util::stream::empty()));
+    assert!(empty.response().await.is_err());
+}
+
+#[tokio::test]
+async fn test_streamed_response_after_iteration() {
+    let chunk = serde_json::from_str::<GenerateContentResponse>(
+        r#"{"candidates":[{"index":0,"content":{"role":"model","parts":[{"text":"Hi"}]},"finishReason":"STOP","safetyRatings":[]}],"usageMetadata":{"promptTokenCount":1,"candidatesTokenCount":1,"totalTokenCount":2}}"#,
+    )
+    .unwrap();
+    // `unfold` panics when polled again after completion, unlike `iter`.
+    let stream = futures_util::stream::unfold(Some(chunk), |chunk| async move {
+        chunk.map(|chunk| (Ok(chunk), None))
+    });
+
+    let mut streamed = StreamedResponse::new(Box::pin(stream));
+    while let Some(chunk) = streamed.next().await {
+        chunk.unwrap();
+    }
+    let response = streamed.response().await.unwrap();
+    assert_eq!(
+        response.candidates[0].content.as_ref().unwrap().parts[0].as_text(),
+        Some("Hi")
+    );
+}
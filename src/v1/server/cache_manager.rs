@@ -0,0 +1,332 @@
+use std::sync::Arc;
+
+use reqwest::{Method, Response};
+
+use crate::v1::{
+    auth::ApiKey,
+    errors::{check_response, decode_response, GenerativeAIError, Result},
+    http::{self, HttpClient},
+    traits::AuthProvider,
+    types::{
+        requests::{Backend, ListParams, RequestOptions},
+        server::caching::{cache_name, CachedContent, ExpireTimeOrTTL, ListCachedContentsResponse},
+    },
+};
+
+/// CacheManager manages [CachedContent] resources through the `cachedContents` endpoints.
+///
+/// Cached content lets a long system instruction or set of contents be processed once and
+/// referenced by name in subsequent requests.
+#[derive(Debug)]
+pub struct CacheManager {
+    auth: Arc<dyn AuthProvider>,
+    client: HttpClient,
+    pub request_options: RequestOptions,
+}
+
+impl CacheManager {
+    pub fn new(api_key: impl Into<ApiKey>, request_options: Option<RequestOptions>) -> Self {
+        Self::from_auth_provider(Arc::new(api_key.into()), request_options)
+    }
+
+    /// Create a manager authenticating its requests with the given provider instead of an API key.
+    pub fn from_auth_provider(
+        auth: Arc<dyn AuthProvider>,
+        request_options: Option<RequestOptions>,
+    ) -> Self {
+        Self {
+            auth,
+            client: HttpClient::default(),
+            request_options: request_options.unwrap_or_default(),
+        }
+    }
+
+    /// Send the requests of this manager through the given client, e.g. to share it with the models.
+    pub fn with_http_client(mut self, client: HttpClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Create a new cached content.
+    ///
+    /// [CachedContent::model] is required, and `models/` is prepended to it when missing.
+    pub async fn create(&self, mut cached_content: CachedContent) -> Result<CachedContent> {
+        if cached_content.model.is_empty() {
+            return Err(GenerativeAIError::Validation(
+                "cached content must specify a model".to_string(),
+            ));
+        }
+        if !cached_content.model.contains('/') {
+            cached_content.model = format!("models/{}", cached_content.model);
+        }
+
+        let response = self
+            ._make_request(Method::POST, "cachedContents", &[], Some(&cached_content))
+            .await?;
+        decode_response(response).await
+    }
+
+    /// List a page of cached contents.
+    pub async fn list(&self, params: Option<ListParams>) -> Result<ListCachedContentsResponse> {
+        let query = params.unwrap_or_default().into_query();
+
+        let response = self
+            ._make_request(Method::GET, "cachedContents", &query, None::<&()>)
+            .await?;
+        decode_response(response).await
+    }
+
+    /// List every cached content, following the pagination until the last page.
+    pub async fn list_all(&self) -> Result<Vec<CachedContent>> {
+        let mut cached_contents = Vec::new();
+        let mut page_token = None;
+        loop {
+            let page = self
+                .list(Some(ListParams {
+                    page_size: None,
+                    page_token,
+                }))
+                .await?;
+            cached_contents.extend(page.cached_contents);
+
+            match page.next_page_token {
+                Some(token) if !token.is_empty() => page_token = Some(token),
+                _ => return Ok(cached_contents),
+            }
+        }
+    }
+
+    /// Get a cached content by name, with or without the `cachedContents/` prefix.
+    pub async fn get(&self, name: &str) -> Result<CachedContent> {
+        let response = self
+            ._make_request(Method::GET, &cache_name(name), &[], None::<&()>)
+            .await?;
+        decode_response(response).await
+    }
+
+    /// Update the expiration of a cached content. Only the expiration of a cached content can be updated.
+    pub async fn update(&self, name: &str, expiration: ExpireTimeOrTTL) -> Result<CachedContent> {
+        let update_mask = match (&expiration.expire_time, &expiration.ttl) {
+            (Some(_), None) => "expireTime",
+            (None, Some(_)) => "ttl",
+            _ => {
+                return Err(GenerativeAIError::Validation(
+                    "exactly one of expire_time and ttl must be set".to_string(),
+                ))
+            }
+        };
+        let body = CachedContent {
+            expiration,
+            ..Default::default()
+        };
+
+        let response = self
+            ._make_request(
+                Method::PATCH,
+                &cache_name(name),
+                &[("updateMask", update_mask.to_string())],
+                Some(&body),
+            )
+            .await?;
+        decode_response(response).await
+    }
+
+    /// Delete a cached content by name, with or without the `cachedContents/` prefix.
+    pub async fn delete(&self, name: &str) -> Result<()> {
+        let response = self
+            ._make_request(Method::DELETE, &cache_name(name), &[], None::<&()>)
+            .await?;
+        check_response(response).await?;
+        Ok(())
+    }
+
+    async fn _make_request<T: serde::Serialize>(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<&T>,
+    ) -> Result<Response> {
+        let url = Backend::GeminiDeveloperApi.url(&self.request_options, path);
+
+        let mut request = self
+            .auth
+            .authorize(
+                &self.client,
+                &self.request_options,
+                self.client.request(method, url),
+            )
+            .await?
+            .query(query);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+
+        http::send(&self.client, request, &self.request_options).await
+    }
+}
+
+#[test]
+fn test_cached_content_wire_format() {
+    use std::time::Duration;
+
+    let cached_content = CachedContent {
+        expiration: ExpireTimeOrTTL::ttl(Duration::from_secs(300)),
+        display_name: Some("docs".to_string()),
+        model: "models/gemini-1.5-flash-001".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(
+        serde_json::to_value(&cached_content).unwrap(),
+        serde_json::json!({
+            "ttl": "300s",
+            "displayName": "docs",
+            "model": "models/gemini-1.5-flash-001",
+        })
+    );
+
+    let cached_content = serde_json::from_str::<CachedContent>(
+        r#"{
+            "name": "cachedContents/abc123",
+            "model": "models/gemini-1.5-flash-001",
+            "createTime": "2024-07-01T10:00:00.000000Z",
+            "updateTime": "2024-07-01T10:00:00.000000Z",
+            "expireTime": "2024-07-01T10:05:00.000000Z",
+            "displayName": "docs",
+            "usageMetadata": { "totalTokenCount": 40000 }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        cached_content.name.as_deref(),
+        Some("cachedContents/abc123")
+    );
+    assert_eq!(
+        cached_content.expiration.expire_time.as_deref(),
+        Some("2024-07-01T10:05:00.000000Z")
+    );
+    assert_eq!(
+        cached_content.usage_metadata.unwrap().total_token_count,
+        40000
+    );
+
+    assert_eq!(cache_name("abc123"), "cachedContents/abc123");
+    assert_eq!(cache_name("cachedContents/abc123"), "cachedContents/abc123");
+}
+
+#[tokio::test]
+async fn test_cache_manager_endpoints() {
+    use std::time::Duration;
+
+    use crate::v1::testing::{MockResponse, MockServer};
+
+    let server = MockServer::start().await.unwrap();
+    let cache_manager = CacheManager::new("api-key", Some(server.request_options()));
+    let cached_content = |id: &str| {
+        serde_json::json!({
+            "name": format!("cachedContents/{id}"),
+            "model": "models/gemini-1.5-flash-001",
+            "expireTime": "2024-07-01T10:05:00.000000Z",
+        })
+    };
+
+    server.mock(
+        Method::POST,
+        "cachedContents",
+        MockResponse::json(cached_content("abc123")),
+    );
+    let created = cache_manager
+        .create(CachedContent {
+            expiration: ExpireTimeOrTTL::ttl(Duration::from_secs(300)),
+            model: "gemini-1.5-flash-001".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(created.name.as_deref(), Some("cachedContents/abc123"));
+    let request = server.requests().pop().unwrap();
+    assert_eq!(
+        request.json(),
+        serde_json::json!({ "ttl": "300s", "model": "models/gemini-1.5-flash-001" })
+    );
+    assert!(cache_manager
+        .create(CachedContent::default())
+        .await
+        .is_err());
+
+    server.mock(
+        Method::GET,
+        "cachedContents/abc123",
+        MockResponse::json(cached_content("abc123")),
+    );
+    let cached = cache_manager.get("abc123").await.unwrap();
+    assert_eq!(cached.model, "models/gemini-1.5-flash-001");
+
+    server.mock(
+        Method::PATCH,
+        "cachedContents/abc123",
+        MockResponse::json(cached_content("abc123")),
+    );
+    cache_manager
+        .update(
+            "cachedContents/abc123",
+            ExpireTimeOrTTL::expire_time("2024-07-01T11:00:00Z"),
+        )
+        .await
+        .unwrap();
+    let request = server.requests().pop().unwrap();
+    assert_eq!(request.query.as_deref(), Some("updateMask=expireTime"));
+    assert_eq!(
+        request.json(),
+        serde_json::json!({ "expireTime": "2024-07-01T11:00:00Z" })
+    );
+    let err = cache_manager
+        .update("abc123", ExpireTimeOrTTL::default())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, GenerativeAIError::Validation(_)));
+
+    server
+        .mock(
+            Method::GET,
+            "cachedContents",
+            MockResponse::json(serde_json::json!({
+                "cachedContents": [cached_content("abc123"), cached_content("def456")],
+                "nextPageToken": "page-2",
+            })),
+        )
+        .mock(
+            Method::GET,
+            "cachedContents",
+            MockResponse::json(serde_json::json!({
+                "cachedContents": [cached_content("ghi789")],
+            })),
+        );
+    let cached_contents = cache_manager.list_all().await.unwrap();
+    let names: Vec<_> = cached_contents
+        .iter()
+        .map(|cached_content| cached_content.name.as_deref().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "cachedContents/abc123",
+            "cachedContents/def456",
+            "cachedContents/ghi789"
+        ]
+    );
+    let requests = server.requests();
+    let queries: Vec<_> = requests[requests.len() - 2..]
+        .iter()
+        .map(|request| request.query.as_deref())
+        .collect();
+    assert_eq!(queries, [None, Some("pageToken=page-2")]);
+
+    server.mock(
+        Method::DELETE,
+        "cachedContents/abc123",
+        MockResponse::json(serde_json::json!({})),
+    );
+    cache_manager.delete("abc123").await.unwrap();
+    assert_eq!(server.pending_fixtures(), 0);
+}
@@ -0,0 +1,709 @@
+use std::{fmt, future::Future, path::Path, sync::Arc, time::Duration};
+
+use reqwest::{Method, Response};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::v1::{
+    auth::ApiKey,
+    errors::{check_response, decode_response, GenerativeAIError, Result},
+    http::{self, HttpClient},
+    media,
+    traits::AuthProvider,
+    types::{
+        content_types::Part,
+        requests::{Backend, ListParams, RequestOptions},
+        server::files::{File, FileMetadata, FileState, ListFilesResponse, UploadFileResponse},
+    },
+};
+
+/// Size of the chunks sent by a resumable upload. Must be a multiple of 256 KiB.
+pub const UPLOAD_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+
+/// Upper bound of the interval between two polls of [FileManager::wait_until_active], unless the initial interval is larger.
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Initial poll interval of the media uploaded by [FileManager::media_part_from_path] and similar.
+const MEDIA_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long [FileManager::media_part_from_path] and similar wait for an uploaded media to be processed.
+pub const MEDIA_PROCESSING_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// FileProcessingError is returned by [FileManager::wait_until_active] when a file does not become usable.
+#[derive(Debug)]
+pub enum FileProcessingError {
+    /// The processing of the file failed.
+    Failed(Box<File>),
+
+    /// The file was still not active when the timeout elapsed.
+    Timeout { name: String, state: FileState },
+}
+
+impl fmt::Display for FileProcessingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Failed(file) => {
+                let message = file
+                    .error
+                    .as_ref()
+                    .and_then(|error| error.message.as_deref())
+                    .unwrap_or("no error message");
+                write!(f, "processing of file {} failed: {message}", file.name)
+            }
+            Self::Timeout { name, state } => {
+                write!(
+                    f,
+                    "file {name} is still {state:?} after the timeout elapsed"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for FileProcessingError {}
+
+/// FileManager uploads and manages [File] resources through the File API.
+///
+/// Uploaded files can be referenced by their [File::uri] in a [FileData][crate::v1::types::content_types::FileData] part,
+/// which is how large videos, audio and PDFs are sent to the model instead of inline data.
+#[derive(Debug)]
+pub struct FileManager {
+    auth: Arc<dyn AuthProvider>,
+    client: HttpClient,
+    pub request_options: RequestOptions,
+}
+
+impl FileManager {
+    pub fn new(api_key: impl Into<ApiKey>, request_options: Option<RequestOptions>) -> Self {
+        Self::from_auth_provider(Arc::new(api_key.into()), request_options)
+    }
+
+    /// Create a manager authenticating its requests with the given provider instead of an API key.
+    pub fn from_auth_provider(
+        auth: Arc<dyn AuthProvider>,
+        request_options: Option<RequestOptions>,
+    ) -> Self {
+        Self {
+            auth,
+            client: HttpClient::default(),
+            request_options: request_options.unwrap_or_default(),
+        }
+    }
+
+    /// Send the requests of this manager through the given client, e.g. to share it with the models.
+    pub fn with_http_client(mut self, client: HttpClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Upload a file from a path.
+    ///
+    /// An empty [FileMetadata::mime_type] is detected from the extension of the file.
+    pub async fn upload_file(
+        &self,
+        path: impl AsRef<Path>,
+        mut metadata: FileMetadata,
+    ) -> Result<UploadFileResponse> {
+        let path = path.as_ref();
+        if metadata.mime_type.is_empty() {
+            metadata.mime_type = media::resolve_mime_type(None, &[], Some(path))?;
+        }
+        let file = tokio::fs::File::open(path).await?;
+        let size = file.metadata().await?.len();
+        self.upload_reader(file, Some(size), metadata).await
+    }
+
+    /// Upload a file from bytes in memory.
+    ///
+    /// An empty [FileMetadata::mime_type] is detected from the bytes.
+    pub async fn upload_bytes(
+        &self,
+        bytes: &[u8],
+        mut metadata: FileMetadata,
+    ) -> Result<UploadFileResponse> {
+        if metadata.mime_type.is_empty() {
+            metadata.mime_type = media::resolve_mime_type(None, bytes, None)?;
+        }
+        self.upload_reader(bytes, Some(bytes.len() as u64), metadata)
+            .await
+    }
+
+    /// Upload a file from an [AsyncRead], using the resumable upload protocol.
+    ///
+    /// The reader is sent in chunks of [UPLOAD_CHUNK_SIZE], so the file is never held in memory as a whole.
+    /// `size` is the total number of bytes, if known in advance.
+    /// [FileMetadata::mime_type] is required, as the content cannot be inspected before it is sent.
+    pub async fn upload_reader<R: AsyncRead + Unpin>(
+        &self,
+        mut reader: R,
+        size: Option<u64>,
+        metadata: FileMetadata,
+    ) -> Result<UploadFileResponse> {
+        if metadata.mime_type.is_empty() {
+            return Err(GenerativeAIError::Validation(
+                "the MIME type of the file to upload is required".to_string(),
+            ));
+        }
+        let upload_url = self._start_upload(size, &metadata).await?;
+
+        let mut offset = 0;
+        let mut chunk = read_chunk(&mut reader).await?;
+        loop {
+            let next = if chunk.len() == UPLOAD_CHUNK_SIZE {
+                read_chunk(&mut reader).await?
+            } else {
+                Vec::new()
+            };
+            let finalize = next.is_empty();
+            let chunk_len = chunk.len() as u64;
+
+            // Chunks are sent once: a failed chunk leaves the upload at an unknown offset.
+            let request = self
+                .client
+                .request(Method::POST, &upload_url)
+                .header(
+                    "X-Goog-Upload-Command",
+                    if finalize {
+                        "upload, finalize"
+                    } else {
+                        "upload"
+                    },
+                )
+                .header("X-Goog-Upload-Offset", offset)
+                .body(chunk);
+            let response = http::send_once(&self.client, request, &self.request_options).await?;
+            if finalize {
+                return decode_response(response).await;
+            }
+            check_response(response).await?;
+            offset += chunk_len;
+            chunk = next;
+        }
+    }
+
+    /// Build a [Part] from a media file: inline when it fits in [MAX_INLINE_SIZE][media::MAX_INLINE_SIZE],
+    /// otherwise uploaded with the File API and referenced by its URI.
+    /// The size is checked for this part only, not for the whole request it is sent in.
+    ///
+    /// The MIME type is detected from the content of the file, or its extension.
+    /// An uploaded file is waited for until it is active, for up to [MEDIA_PROCESSING_TIMEOUT].
+    pub async fn media_part_from_path(&self, path: impl AsRef<Path>) -> Result<Part> {
+        let path = path.as_ref();
+        let file = tokio::fs::File::open(path).await?;
+        let size = file.metadata().await?.len();
+        self._media_part(file, Some(size), None, Some(path)).await
+    }
+
+    /// Build a [Part] from media bytes, see [FileManager::media_part_from_path].
+    /// The MIME type is detected from the bytes when not given.
+    pub async fn media_part_from_bytes(
+        &self,
+        bytes: &[u8],
+        mime_type: Option<&str>,
+    ) -> Result<Part> {
+        self._media_part(bytes, Some(bytes.len() as u64), mime_type, None)
+            .await
+    }
+
+    /// Build a [Part] from media read from an [AsyncRead], see [FileManager::media_part_from_path].
+    /// The MIME type is detected from the content when not given.
+    ///
+    /// Up to [MAX_INLINE_BYTES][media::MAX_INLINE_BYTES] + 1 bytes are buffered to decide whether the media can be inlined,
+    /// the rest of a larger media is streamed to the File API.
+    pub async fn media_part_from_reader<R: AsyncRead + Unpin>(
+        &self,
+        reader: R,
+        mime_type: Option<&str>,
+    ) -> Result<Part> {
+        self._media_part(reader, None, mime_type, None).await
+    }
+
+    /// Inline the media when small enough, otherwise upload it. The MIME type is detected when not given.
+    async fn _media_part<R: AsyncRead + Unpin>(
+        &self,
+        mut reader: R,
+        size: Option<u64>,
+        mime_type: Option<&str>,
+        path: Option<&Path>,
+    ) -> Result<Part> {
+        let head = media::read_inline(&mut reader).await?;
+        let metadata = FileMetadata {
+            mime_type: media::resolve_mime_type(mime_type, &head, path)?,
+            display_name: path
+                .and_then(Path::file_name)
+                .map(|name| name.to_string_lossy().into_owned()),
+            ..Default::default()
+        };
+        if head.len() <= media::MAX_INLINE_BYTES {
+            return Ok(Part::inline_data(metadata.mime_type, head));
+        }
+
+        let file = self
+            .upload_reader(head.as_slice().chain(reader), size, metadata)
+            .await?
+            .file;
+        let file = match file.state {
+            FileState::Active => file,
+            _ => {
+                self.wait_until_active(&file.name, MEDIA_POLL_INTERVAL, MEDIA_PROCESSING_TIMEOUT)
+                    .await?
+            }
+        };
+        Ok(file.to_part())
+    }
+
+    /// Start a resumable upload, returning the URL to send the bytes to.
+    async fn _start_upload(&self, size: Option<u64>, metadata: &FileMetadata) -> Result<String> {
+        let backend = Backend::GeminiDeveloperApi;
+        let url = format!(
+            "{}/upload/{}/files",
+            backend.base_url(&self.request_options),
+            backend.api_version(&self.request_options)
+        );
+
+        let mut request = self
+            .auth
+            .authorize(
+                &self.client,
+                &self.request_options,
+                self.client.request(Method::POST, url),
+            )
+            .await?
+            .header("X-Goog-Upload-Protocol", "resumable")
+            .header("X-Goog-Upload-Command", "start")
+            .header("X-Goog-Upload-Header-Content-Type", &metadata.mime_type)
+            .json(&serde_json::json!({ "file": metadata }));
+        if let Some(size) = size {
+            request = request.header("X-Goog-Upload-Header-Content-Length", size);
+        }
+
+        let response =
+            check_response(http::send(&self.client, request, &self.request_options).await?).await?;
+        response
+            .headers()
+            .get("x-goog-upload-url")
+            .and_then(|url| url.to_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                GenerativeAIError::IncompleteResponse(
+                    "upload start response is missing the x-goog-upload-url header".to_string(),
+                )
+            })
+    }
+
+    /// Wait until an uploaded file is processed and can be referenced in a prompt.
+    ///
+    /// The file state is polled, starting after `poll_interval` and backing off by 1.5x after each poll.
+    /// Fails with a [FileProcessingError] if the file reaches [FileState::Failed] or is not active within `timeout`.
+    pub async fn wait_until_active(
+        &self,
+        name: &str,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<File> {
+        poll_until_active(|| self.get_file(name), poll_interval, timeout).await
+    }
+
+    /// Get the metadata of a file by name, with or without the `files/` prefix.
+    pub async fn get_file(&self, name: &str) -> Result<File> {
+        let response = self
+            ._make_request(Method::GET, &file_name(name), &[])
+            .await?;
+        decode_response(response).await
+    }
+
+    /// List a page of the files owned by the project.
+    pub async fn list_files(&self, params: Option<ListParams>) -> Result<ListFilesResponse> {
+        let query = params.unwrap_or_default().into_query();
+
+        let response = self._make_request(Method::GET, "files", &query).await?;
+        decode_response(response).await
+    }
+
+    /// Delete a file by name, with or without the `files/` prefix.
+    pub async fn delete_file(&self, name: &str) -> Result<()> {
+        let response = self
+            ._make_request(Method::DELETE, &file_name(name), &[])
+            .await?;
+        check_response(response).await?;
+        Ok(())
+    }
+
+    async fn _make_request(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<Response> {
+        let url = Backend::GeminiDeveloperApi.url(&self.request_options, path);
+
+        let request = self
+            .auth
+            .authorize(
+                &self.client,
+                &self.request_options,
+                self.client.request(method, url),
+            )
+            .await?
+            .query(query);
+
+        http::send(&self.client, request, &self.request_options).await
+    }
+}
+
+async fn poll_until_active<F, Fut>(
+    mut get_file: F,
+    poll_interval: Duration,
+    timeout: Duration,
+) -> Result<File>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<File>>,
+{
+    let deadline = tokio::time::Instant::now() + timeout;
+    let max_interval = poll_interval.max(MAX_POLL_INTERVAL);
+    let mut interval = poll_interval;
+
+    loop {
+        let file = get_file().await?;
+        match file.state {
+            FileState::Active => return Ok(file),
+            FileState::Failed => return Err(FileProcessingError::Failed(Box::new(file)).into()),
+            _ => {}
+        }
+
+        let now = tokio::time::Instant::now();
+        if now >= deadline {
+            return Err(FileProcessingError::Timeout {
+                name: file.name,
+                state: file.state,
+            }
+            .into());
+        }
+        tokio::time::sleep(interval.min(deadline - now)).await;
+        interval = interval.mul_f32(1.5).min(max_interval);
+    }
+}
+
+/// Read up to [UPLOAD_CHUNK_SIZE] bytes, stopping early only at the end of the reader.
+async fn read_chunk<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Vec<u8>> {
+    let mut chunk = Vec::with_capacity(UPLOAD_CHUNK_SIZE);
+    while chunk.len() < UPLOAD_CHUNK_SIZE {
+        let read = (&mut *reader)
+            .take((UPLOAD_CHUNK_SIZE - chunk.len()) as u64)
+            .read_to_end(&mut chunk)
+            .await?;
+        if read == 0 {
+            break;
+        }
+    }
+    Ok(chunk)
+}
+
+/// Prepend `files/` to a file name when missing.
+fn file_name(name: &str) -> String {
+    if name.starts_with("files/") {
+        name.to_string()
+    } else {
+        format!("files/{name}")
+    }
+}
+
+#[tokio::test]
+async fn test_read_chunk() {
+    let bytes = vec![7u8; UPLOAD_CHUNK_SIZE + 10];
+    let mut reader = bytes.as_slice();
+
+    assert_eq!(
+        read_chunk(&mut reader).await.unwrap().len(),
+        UPLOAD_CHUNK_SIZE
+    );
+    assert_eq!(read_chunk(&mut reader).await.unwrap().len(), 10);
+    assert!(read_chunk(&mut reader).await.unwrap().is_empty());
+}
+
+#[test]
+fn test_file_wire_format() {
+    use crate::v1::types::server::files::FileState;
+
+    let response = serde_json::from_str::<UploadFileResponse>(
+        r#"{
+            "file": {
+                "name": "files/abc-123",
+                "displayName": "Big Buck Bunny",
+                "mimeType": "video/mp4",
+                "sizeBytes": "64657027",
+                "createTime": "2024-07-01T10:00:00.000000Z",
+                "updateTime": "2024-07-01T10:00:00.000000Z",
+                "expirationTime": "2024-07-03T10:00:00.000000Z",
+                "sha256Hash": "ZjY2NjQ4NjM=",
+                "uri": "https://generativelanguage.googleapis.com/v1beta/files/abc-123",
+                "state": "PROCESSING"
+            }
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(response.file.size_bytes, Some(64657027));
+    assert_eq!(response.file.state, FileState::Processing);
+    assert_eq!(file_name("abc-123"), "files/abc-123");
+}
+
+#[tokio::test]
+async fn test_poll_until_active() {
+    let file = |state: &str| -> Result<File> {
+        Ok(serde_json::from_value(serde_json::json!({
+            "name": "files/abc-123",
+            "mimeType": "video/mp4",
+            "uri": "https://generativelanguage.googleapis.com/v1beta/files/abc-123",
+            "state": state,
+            "error": { "code": 3, "message": "Unsupported codec" },
+        }))
+        .unwrap())
+    };
+    let interval = Duration::from_millis(1);
+
+    let mut states = vec!["ACTIVE", "PROCESSING", "PROCESSING"];
+    let active = poll_until_active(
+        || std::future::ready(file(states.pop().unwrap())),
+        interval,
+        Duration::from_secs(5),
+    )
+    .await
+    .unwrap();
+    assert!(states.is_empty());
+    assert!(matches!(
+        active.to_part(),
+        Part::FileData { file_data, .. } if file_data.mime_type == "video/mp4"
+    ));
+
+    let mut states = vec!["FAILED", "PROCESSING"];
+    let err = poll_until_active(
+        || std::future::ready(file(states.pop().unwrap())),
+        interval,
+        Duration::from_secs(5),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        GenerativeAIError::FileProcessing(FileProcessingError::Failed(_))
+    ));
+    assert!(err.to_string().contains("Unsupported codec"));
+
+    let err = poll_until_active(
+        || std::future::ready(file("PROCESSING")),
+        interval,
+        interval,
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        GenerativeAIError::FileProcessing(FileProcessingError::Timeout { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_upload_reader() {
+    use crate::v1::testing::{MockResponse, MockServer};
+
+    let server = MockServer::start().await.unwrap();
+    let file_manager = FileManager::new("api-key", Some(server.request_options()));
+    let upload_url = format!("{}/upload-session", server.base_url());
+    server
+        .mock(
+            Method::POST,
+            "upload/files",
+            MockResponse::json(serde_json::json!({})).with_header("x-goog-upload-url", &upload_url),
+        )
+        .mock(
+            Method::POST,
+            "upload-session",
+            MockResponse::json(serde_json::json!({})),
+        )
+        .mock(
+            Method::POST,
+            "upload-session",
+            MockResponse::json(serde_json::json!({})),
+        )
+        .mock(
+            Method::POST,
+            "upload-session",
+            MockResponse::json(serde_json::json!({
+                "file": {
+                    "name": "files/abc-123",
+                    "mimeType": "video/mp4",
+                    "uri": "https://generativelanguage.googleapis.com/v1beta/files/abc-123",
+                    "state": "PROCESSING",
+                }
+            })),
+        );
+
+    let size = 2 * UPLOAD_CHUNK_SIZE + 1;
+    let metadata = FileMetadata {
+        display_name: Some("video.mp4".to_string()),
+        mime_type: "video/mp4".to_string(),
+        ..Default::default()
+    };
+    let response = file_manager
+        .upload_reader(
+            tokio::io::repeat(0).take(size as u64),
+            Some(size as u64),
+            metadata,
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.file.name, "files/abc-123");
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 4);
+    let start = &requests[0];
+    assert_eq!(start.header("x-goog-upload-protocol"), Some("resumable"));
+    assert_eq!(start.header("x-goog-upload-command"), Some("start"));
+    assert_eq!(
+        start.header("x-goog-upload-header-content-type"),
+        Some("video/mp4")
+    );
+    assert_eq!(
+        start.header("x-goog-upload-header-content-length"),
+        Some(size.to_string().as_str())
+    );
+    assert_eq!(
+        start.json(),
+        serde_json::json!({ "file": { "displayName": "video.mp4", "mimeType": "video/mp4" } })
+    );
+
+    let chunks: Vec<_> = requests[1..]
+        .iter()
+        .map(|chunk| {
+            (
+                chunk.header("x-goog-upload-command").unwrap(),
+                chunk.header("x-goog-upload-offset").unwrap().to_string(),
+                chunk.body.len(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        chunks,
+        [
+            ("upload", "0".to_string(), UPLOAD_CHUNK_SIZE),
+            ("upload", UPLOAD_CHUNK_SIZE.to_string(), UPLOAD_CHUNK_SIZE),
+            ("upload, finalize", (2 * UPLOAD_CHUNK_SIZE).to_string(), 1),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_media_part() {
+    use crate::v1::testing::{MockResponse, MockServer};
+
+    let server = MockServer::start().await.unwrap();
+    let file_manager = FileManager::new("api-key", Some(server.request_options()));
+
+    // Small media are inlined without any request.
+    let part = file_manager
+        .media_part_from_bytes(b"\x89PNG\r\n\x1a\n", None)
+        .await
+        .unwrap();
+    assert!(
+        matches!(&part, Part::InlineData { inline_data: blob, .. } if blob.mime_type == "image/png")
+    );
+    assert!(server.requests().is_empty());
+
+    // Larger media are uploaded with the File API, then referenced by URI.
+    let upload_url = format!("{}/upload-session", server.base_url());
+    server
+        .mock(
+            Method::POST,
+            "upload/files",
+            MockResponse::json(serde_json::json!({})).with_header("x-goog-upload-url", &upload_url),
+        )
+        .mock(
+            Method::POST,
+            "upload-session",
+            MockResponse::json(serde_json::json!({})),
+        )
+        .mock(
+            Method::POST,
+            "upload-session",
+            MockResponse::json(serde_json::json!({
+                "file": {
+                    "name": "files/abc-123",
+                    "mimeType": "video/mp4",
+                    "uri": "https://generativelanguage.googleapis.com/v1beta/files/abc-123",
+                    "state": "ACTIVE",
+                }
+            })),
+        );
+    let reader = tokio::io::repeat(0).take(media::MAX_INLINE_BYTES as u64 + 1);
+    let part = file_manager
+        .media_part_from_reader(reader, Some("video/mp4"))
+        .await
+        .unwrap();
+    assert!(matches!(
+        &part,
+        Part::FileData { file_data, .. } if file_data.uri.ends_with("files/abc-123")
+    ));
+    assert_eq!(server.pending_fixtures(), 0);
+    let requests = server.requests();
+    assert_eq!(
+        requests[0].header("x-goog-upload-header-content-type"),
+        Some("video/mp4")
+    );
+    let uploaded: usize = requests[1..].iter().map(|request| request.body.len()).sum();
+    assert_eq!(uploaded, media::MAX_INLINE_BYTES + 1);
+}
+
+#[tokio::test]
+async fn test_upload_mime_type() {
+    use crate::v1::testing::{MockResponse, MockServer};
+
+    let server = MockServer::start().await.unwrap();
+    let file_manager = FileManager::new("api-key", Some(server.request_options()));
+
+    // A missing MIME type which cannot be detected is rejected before any request is sent.
+    let err = file_manager
+        .upload_reader(&b"%PDF-1.7"[..], None, FileMetadata::default())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, GenerativeAIError::Validation(_)));
+    let err = file_manager
+        .upload_bytes(b"plain data", FileMetadata::default())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, GenerativeAIError::Validation(_)));
+    assert!(server.requests().is_empty());
+
+    // Otherwise it is detected from the bytes.
+    let upload_url = format!("{}/upload-session", server.base_url());
+    server
+        .mock(
+            Method::POST,
+            "upload/files",
+            MockResponse::json(serde_json::json!({})).with_header("x-goog-upload-url", &upload_url),
+        )
+        .mock(
+            Method::POST,
+            "upload-session",
+            MockResponse::json(serde_json::json!({
+                "file": {
+                    "name": "files/abc-123",
+                    "mimeType": "application/pdf",
+                    "uri": "https://generativelanguage.googleapis.com/v1beta/files/abc-123",
+                }
+            })),
+        );
+    file_manager
+        .upload_bytes(b"%PDF-1.7", FileMetadata::default())
+        .await
+        .unwrap();
+    let requests = server.requests();
+    assert_eq!(
+        requests[0].header("x-goog-upload-header-content-type"),
+        Some("application/pdf")
+    );
+    assert_eq!(
+        requests[0].json(),
+        serde_json::json!({ "file": { "mimeType": "application/pdf" } })
+    );
+}
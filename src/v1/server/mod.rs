@@ -0,0 +1,2 @@
+pub mod cache_manager;
+pub mod file_manager;
@@ -0,0 +1,476 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use futures_util::future::BoxFuture;
+use reqwest::{header::CONTENT_TYPE, Request, Response};
+use tokio::sync::Mutex;
+
+use crate::v1::{
+    auth::API_KEY_HEADER,
+    errors::{GenerativeAIError, Result},
+    traits::Transport,
+};
+
+/// Placeholder replacing credentials in a cassette.
+const REDACTED: &str = "<redacted>";
+
+/// Headers never written to a cassette.
+const SENSITIVE_HEADERS: [&str; 2] = [API_KEY_HEADER, "authorization"];
+
+/// Form fields of OAuth2 token requests redacted from a cassette, e.g. the JWT signed by a service account.
+const SENSITIVE_FORM_FIELDS: [&str; 4] = [
+    "assertion",
+    "client_secret",
+    "refresh_token",
+    "subject_token",
+];
+
+/// Fields of OAuth2 token responses redacted from a cassette.
+const SENSITIVE_RESPONSE_FIELDS: [&str; 3] = ["access_token", "id_token", "refresh_token"];
+
+/// CassetteMode tells whether a [CassetteTransport] records or replays HTTP interactions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Send the requests to the network, and write every interaction to the cassette file.
+    Record,
+
+    /// Answer the requests from the cassette file, without any network access.
+    Replay,
+}
+
+/// CassetteTransport is a [Transport] recording HTTP interactions to a cassette file, or replaying them.
+///
+/// This captures the real behavior of the API once, to run integration tests offline afterwards.
+/// Use it with [HttpClient::from_transport][crate::v1::http::HttpClient::from_transport].
+///
+/// - In [CassetteMode::Record], requests are sent with a [reqwest::Client] and the whole response,
+///   including a streamed body, is buffered and appended to the cassette, which is rewritten after each interaction.
+///   The `x-goog-api-key` and `Authorization` headers are not recorded, and the API key is scrubbed from URLs and bodies.
+///   OAuth2 token exchanges, e.g. by a [ServiceAccount][crate::v1::auth::ServiceAccount], are recorded with their
+///   assertion and tokens redacted, so they replay with a placeholder token.
+/// - In [CassetteMode::Replay], a request is answered by the first unused interaction with the same method, URL and body.
+///   A request matching no interaction fails with a [GenerativeAIError::Cassette] naming the request and cassette.
+#[derive(Debug)]
+pub struct CassetteTransport {
+    path: PathBuf,
+    mode: CassetteMode,
+    client: reqwest::Client,
+    state: Mutex<CassetteState>,
+}
+
+#[derive(Debug, Default)]
+struct CassetteState {
+    cassette: Cassette,
+    /// Whether each interaction has already been replayed.
+    used: Vec<bool>,
+}
+
+/// Cassette is the content of a cassette file.
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+/// Interaction is a request and its response, as recorded in a [Cassette].
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+/// RecordedRequest is a request of an [Interaction], without credentials.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub body: String,
+}
+
+/// RecordedResponse is the response of an [Interaction].
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub body: String,
+}
+
+impl CassetteTransport {
+    /// Record the interactions to the cassette at `path`, sending the requests with `client`.
+    /// An existing cassette is overwritten.
+    pub fn record(path: impl AsRef<Path>, client: reqwest::Client) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            mode: CassetteMode::Record,
+            client,
+            state: Mutex::new(CassetteState::default()),
+        }
+    }
+
+    /// Replay the interactions of the cassette at `path`.
+    pub async fn replay(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let content = tokio::fs::read_to_string(&path).await?;
+        let cassette: Cassette = serde_json::from_str(&content).map_err(|err| {
+            GenerativeAIError::Cassette(format!("invalid cassette {}: {err}", path.display()))
+        })?;
+
+        Ok(Self {
+            path,
+            mode: CassetteMode::Replay,
+            client: reqwest::Client::new(),
+            state: Mutex::new(CassetteState {
+                used: vec![false; cassette.interactions.len()],
+                cassette,
+            }),
+        })
+    }
+
+    /// Record or replay depending on `mode`, e.g. chosen from an environment variable in CI.
+    pub async fn new(path: impl AsRef<Path>, mode: CassetteMode) -> Result<Self> {
+        match mode {
+            CassetteMode::Record => Ok(Self::record(path, reqwest::Client::new())),
+            CassetteMode::Replay => Self::replay(path).await,
+        }
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    async fn _record(&self, request: Request) -> Result<Response> {
+        let recorded_request = RecordedRequest::from(&request);
+        let response = self.client.execute(request).await?;
+
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let body = scrub_token_response(&String::from_utf8_lossy(&response.bytes().await?));
+        let recorded_response = RecordedResponse {
+            status,
+            headers,
+            body,
+        };
+
+        let mut state = self.state.lock().await;
+        state.cassette.interactions.push(Interaction {
+            request: recorded_request,
+            response: recorded_response.clone(),
+        });
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let content = serde_json::to_string_pretty(&state.cassette).unwrap_or_default();
+        tokio::fs::write(&self.path, content).await?;
+
+        recorded_response.to_response()
+    }
+
+    async fn _replay(&self, request: Request) -> Result<Response> {
+        let recorded_request = RecordedRequest::from(&request);
+
+        let mut state = self.state.lock().await;
+        let CassetteState { cassette, used } = &mut *state;
+        let index =
+            cassette
+                .interactions
+                .iter()
+                .zip(used.iter())
+                .position(|(interaction, used)| {
+                    !used && interaction.request.matches(&recorded_request)
+                });
+        let Some(index) = index else {
+            let message = format!(
+                "cassette {} has no unused interaction for {} {}",
+                self.path.display(),
+                recorded_request.method,
+                recorded_request.url
+            );
+            log::error!("{message}");
+            return Err(GenerativeAIError::Cassette(message));
+        };
+
+        used[index] = true;
+        cassette.interactions[index].response.to_response()
+    }
+}
+
+impl Transport for CassetteTransport {
+    fn execute(&self, request: Request) -> BoxFuture<'_, Result<Response>> {
+        Box::pin(async move {
+            match self.mode {
+                CassetteMode::Record => self._record(request).await,
+                CassetteMode::Replay => self._replay(request).await,
+            }
+        })
+    }
+}
+
+impl RecordedRequest {
+    /// Whether a request is a replay of this one. Headers are ignored.
+    fn matches(&self, other: &RecordedRequest) -> bool {
+        self.method == other.method && self.url == other.url && self.body == other.body
+    }
+}
+
+impl From<&Request> for RecordedRequest {
+    /// Record a request, scrubbing its credentials.
+    fn from(request: &Request) -> Self {
+        let api_key = request
+            .headers()
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .filter(|api_key| !api_key.is_empty());
+        let scrub = |value: &str| match api_key {
+            Some(api_key) => value.replace(api_key, REDACTED),
+            None => value.to_string(),
+        };
+
+        let mut url = request.url().clone();
+        let query: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(name, value)| match name.as_ref() {
+                "key" => (name.into_owned(), REDACTED.to_string()),
+                _ => (name.into_owned(), value.into_owned()),
+            })
+            .collect();
+        if query.is_empty() {
+            url.set_query(None);
+        } else {
+            url.query_pairs_mut().clear().extend_pairs(query);
+        }
+
+        let headers = request
+            .headers()
+            .iter()
+            .filter(|(name, value)| {
+                !value.is_sensitive() && !SENSITIVE_HEADERS.contains(&name.as_str())
+            })
+            .filter_map(|(name, value)| Some((name.to_string(), scrub(value.to_str().ok()?))))
+            .collect();
+        let is_form = request
+            .headers()
+            .get(CONTENT_TYPE)
+            .is_some_and(|value| value.as_bytes() == b"application/x-www-form-urlencoded");
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|body| scrub(&String::from_utf8_lossy(body)))
+            .map(|body| if is_form { scrub_form(&body) } else { body })
+            .unwrap_or_default();
+
+        Self {
+            method: request.method().to_string(),
+            url: scrub(url.as_str()),
+            headers,
+            body,
+        }
+    }
+}
+
+/// Redact the credentials of an OAuth2 token request body, keeping the other fields to match it on replay.
+fn scrub_form(body: &str) -> String {
+    body.split('&')
+        .map(|field| match field.split_once('=') {
+            Some((name, _)) if SENSITIVE_FORM_FIELDS.contains(&name) => {
+                format!("{name}={REDACTED}")
+            }
+            _ => field.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Redact the tokens of an OAuth2 token response body. Other bodies are kept as is.
+fn scrub_token_response(body: &str) -> String {
+    let Ok(serde_json::Value::Object(mut fields)) = serde_json::from_str(body) else {
+        return body.to_string();
+    };
+    let mut scrubbed = false;
+    for name in SENSITIVE_RESPONSE_FIELDS {
+        if let Some(value) = fields.get_mut(name) {
+            *value = REDACTED.into();
+            scrubbed = true;
+        }
+    }
+    if scrubbed {
+        serde_json::Value::Object(fields).to_string()
+    } else {
+        body.to_string()
+    }
+}
+
+impl RecordedResponse {
+    fn to_response(&self) -> Result<Response> {
+        let mut response = http::Response::builder().status(self.status);
+        for (name, value) in &self.headers {
+            response = response.header(name, value);
+        }
+        let response = response.body(self.body.clone()).map_err(|err| {
+            GenerativeAIError::Cassette(format!("invalid recorded response: {err}"))
+        })?;
+        Ok(response.into())
+    }
+}
+
+#[tokio::test]
+async fn test_record_and_replay() {
+    use futures_util::StreamExt;
+
+    use crate::v1::{
+        genai::GoogleGenerativeAI,
+        http::HttpClient,
+        testing::{text_response, MockServer},
+        types::{content_types::Part, model::ModelParams, requests::RequestOptions},
+    };
+
+    let path = std::env::temp_dir().join(format!(
+        "genai-cassette-{}-{:?}.json",
+        std::process::id(),
+        std::time::SystemTime::now()
+    ));
+    let part = Part::from("Hello Gemini");
+
+    let server = MockServer::start().await.unwrap();
+    server.mock_generate_content("gemini-1.5-flash", text_response("Hello!"));
+    server.mock_stream_generate_content(
+        "gemini-1.5-flash",
+        vec![text_response("Hello"), text_response(" again!")],
+    );
+    let request_options = server.request_options();
+
+    let transport = CassetteTransport::record(&path, reqwest::Client::new());
+    let model = GoogleGenerativeAI::with_http_client(
+        "secret-api-key",
+        HttpClient::from_transport(transport),
+    )
+    .get_generative_model(ModelParams::new("gemini-1.5-flash"), Some(request_options));
+    model.generate_content(vec![part.clone()]).await.unwrap();
+    model
+        .generate_content_stream(vec![part.clone()])
+        .await
+        .unwrap()
+        .response()
+        .await
+        .unwrap();
+    drop(server);
+
+    let cassette = std::fs::read_to_string(&path).unwrap();
+    assert!(!cassette.contains("secret-api-key"));
+    assert!(cassette.contains("data: "));
+
+    let transport = CassetteTransport::replay(&path).await.unwrap();
+    let model = GoogleGenerativeAI::with_http_client(
+        "another-api-key",
+        HttpClient::from_transport(transport),
+    )
+    .get_generative_model(
+        ModelParams::new("gemini-1.5-flash"),
+        Some(RequestOptions {
+            base_url: Some(model.request_options.base_url.clone().unwrap()),
+            ..Default::default()
+        }),
+    );
+    let response = model.generate_content(vec![part.clone()]).await.unwrap();
+    assert_eq!(
+        response.candidates[0].content.as_ref().unwrap().parts[0].as_text(),
+        Some("Hello!")
+    );
+    let chunks: Vec<_> = model
+        .generate_content_stream(vec![part.clone()])
+        .await
+        .unwrap()
+        .collect()
+        .await;
+    assert_eq!(chunks.len(), 2);
+
+    // Every interaction was replayed, so the same request is now unmatched.
+    let err = model.generate_content(vec![part]).await.unwrap_err();
+    assert!(matches!(err, GenerativeAIError::Cassette(_)));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_record_and_replay_service_account() {
+    use crate::v1::{
+        auth::ServiceAccount,
+        genai::GoogleGenerativeAI,
+        http::HttpClient,
+        testing::{text_response, MockResponse, MockServer, SERVICE_ACCOUNT_KEY},
+        types::model::ModelParams,
+    };
+
+    let path = std::env::temp_dir().join(format!(
+        "genai-cassette-sa-{}-{:?}.json",
+        std::process::id(),
+        std::time::SystemTime::now()
+    ));
+    let server = MockServer::start().await.unwrap();
+    server
+        .mock(
+            reqwest::Method::POST,
+            "token",
+            MockResponse::json(serde_json::json!({
+                "access_token": "ya29.secret-token",
+                "expires_in": 3599,
+                "token_type": "Bearer",
+            })),
+        )
+        .mock_generate_content("gemini-1.5-flash", text_response("Hello!"));
+    let token_endpoint = format!("{}/token", server.base_url());
+    let service_account = || {
+        ServiceAccount::from_json(SERVICE_ACCOUNT_KEY)
+            .unwrap()
+            .with_token_endpoint(&token_endpoint)
+    };
+
+    let transport = CassetteTransport::record(&path, reqwest::Client::new());
+    let genai = GoogleGenerativeAI::with_auth_provider(
+        service_account(),
+        HttpClient::from_transport(transport),
+    );
+    let model = genai.get_generative_model(
+        ModelParams::new("gemini-1.5-flash"),
+        Some(server.request_options()),
+    );
+    model.generate_content("Hello Gemini").await.unwrap();
+    let base_url = server.base_url().to_string();
+    drop(server);
+
+    let cassette = std::fs::read_to_string(&path).unwrap();
+    assert!(!cassette.contains("ya29.secret-token"));
+    // A signed JWT starts with its base64 encoded `{"` header.
+    assert!(!cassette.contains("eyJ"));
+    assert!(cassette.contains("assertion=<redacted>"));
+
+    // A new JWT is signed on replay, yet the token exchange matches the recording.
+    let transport = CassetteTransport::replay(&path).await.unwrap();
+    let genai = GoogleGenerativeAI::with_auth_provider(
+        service_account(),
+        HttpClient::from_transport(transport),
+    );
+    let model = genai.get_generative_model(
+        ModelParams::new("gemini-1.5-flash"),
+        Some(crate::v1::types::requests::RequestOptions {
+            base_url: Some(base_url),
+            ..Default::default()
+        }),
+    );
+    let response = model.generate_content("Hello Gemini").await.unwrap();
+    assert_eq!(
+        response.candidates[0].content.as_ref().unwrap().parts[0].as_text(),
+        Some("Hello!")
+    );
+
+    std::fs::remove_file(&path).unwrap();
+}
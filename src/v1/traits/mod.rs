@@ -1,3 +1,39 @@
+use futures_util::future::BoxFuture;
+use reqwest::RequestBuilder;
+
+use super::{errors::Result, http::HttpClient, types::requests::RequestOptions};
+
 pub trait Stringify {
     fn to_str(&self) -> &'static str;
 }
+
+/// Transport sends the HTTP requests of the SDK.
+///
+/// It is implemented by [reqwest::Client], and can be implemented to plug in a test double or another HTTP stack,
+/// see [HttpClient::from_transport][crate::v1::http::HttpClient::from_transport].
+/// Implementations should honor [reqwest::Request::timeout].
+pub trait Transport: std::fmt::Debug + Send + Sync {
+    /// Send a request, resolving once the response headers are received.
+    fn execute(&self, request: reqwest::Request) -> BoxFuture<'_, Result<reqwest::Response>>;
+}
+
+impl Transport for reqwest::Client {
+    fn execute(&self, request: reqwest::Request) -> BoxFuture<'_, Result<reqwest::Response>> {
+        Box::pin(async move { Ok(reqwest::Client::execute(self, request).await?) })
+    }
+}
+
+/// AuthProvider adds credentials to the HTTP requests of the SDK.
+///
+/// It is implemented by [ApiKey][crate::v1::auth::ApiKey], [BearerToken][crate::v1::auth::BearerToken]
+/// and [ServiceAccount][crate::v1::auth::ServiceAccount].
+pub trait AuthProvider: std::fmt::Debug + Send + Sync {
+    /// Add credentials to a request. `client` can be used to obtain them, e.g. to exchange a token,
+    /// along with the `request_options` of the request being authorized.
+    fn authorize<'a>(
+        &'a self,
+        client: &'a HttpClient,
+        request_options: &'a RequestOptions,
+        request: RequestBuilder,
+    ) -> BoxFuture<'a, Result<RequestBuilder>>;
+}
@@ -0,0 +1,867 @@
+//! Golden JSON of the request and response shapes, as documented by the REST API reference.
+
+use std::collections::HashMap;
+
+use serde_json::json;
+
+use super::{
+    content_types::{
+        CodeExecution, CodeExecutionResult, CodeExecutionResultOutcome, Content, ExecutableCode,
+        ExecutableCodeLanguage, FileData, FunctionCalingConfig, FunctionCall, FunctionCallingMode,
+        FunctionDeclaration, FunctionResponse, GenerativeContentBlob, Part, Role, Tool, ToolConfig,
+    },
+    generation_types::{GenerationConfig, MimeType},
+    requests::{
+        BatchEmbedContentsRequest, CountTokensRequest, EmbedContentRequest, GenerateContentRequest,
+        TaskType,
+    },
+    responses::{
+        BlockReason, CountTokensResponse, EmbedContentResponse, FinishReason,
+        GenerateContentResponse,
+    },
+    safety_types::{HarmBlockThreshold, HarmCategory, HarmProbability, SafetySetting},
+    schema::{Schema, SchemaType},
+    server::{
+        caching::{CachedContent, ExpireTimeOrTTL},
+        files::{File, FileMetadata, FileState},
+    },
+};
+
+fn schema(r#type: SchemaType) -> Schema {
+    Schema {
+        r#type,
+        format: None,
+        description: None,
+        nullable: None,
+        r#enum: None,
+        items: None,
+        properties: None,
+        required: None,
+        example: None,
+    }
+}
+
+#[test]
+fn test_generate_content_request() {
+    let request = GenerateContentRequest {
+        model: "models/gemini-1.5-flash".to_string(),
+        contents: vec![
+            Content {
+                role: Role::User,
+                parts: vec![
+                    Part::from("What is in these files?"),
+                    Part::from(GenerativeContentBlob {
+                        mime_type: "image/png".to_string(),
+                        data: "iVBORw0KGgo=".to_string(),
+                        extra: Default::default(),
+                    }),
+                    Part::from(FileData {
+                        mime_type: "application/pdf".to_string(),
+                        uri: "gs://bucket/report.pdf".to_string(),
+                        extra: Default::default(),
+                    }),
+                ],
+                extra: Default::default(),
+            },
+            Content {
+                role: Role::Model,
+                parts: vec![
+                    Part::from(FunctionCall {
+                        name: "get_weather".to_string(),
+                        args: HashMap::from([("city".to_string(), json!("Paris"))]),
+                        extra: Default::default(),
+                    }),
+                    Part::from(ExecutableCode {
+                        code: "print(1 + 1)".to_string(),
+                        language: ExecutableCodeLanguage::Python,
+                        extra: Default::default(),
+                    }),
+                    Part::from(CodeExecutionResult {
+                        outcome: CodeExecutionResultOutcome::Ok,
+                        output: "2\n".to_string(),
+                        extra: Default::default(),
+                    }),
+                ],
+                extra: Default::default(),
+            },
+            Content {
+                role: Role::User,
+                parts: vec![Part::from(FunctionResponse {
+                    name: "get_weather".to_string(),
+                    response: HashMap::from([("temperature".to_string(), json!(21))]),
+                    extra: Default::default(),
+                })],
+                extra: Default::default(),
+            },
+        ],
+        generation_config: Some(GenerationConfig {
+            candidate_count: Some(1),
+            stop_sequences: Some(vec!["END".to_string()]),
+            max_output_tokens: Some(1024),
+            temperature: Some(0.5),
+            top_p: Some(0.25),
+            top_k: Some(40),
+            response_mime_type: Some(MimeType::ApplicationJson),
+            response_schema: Some(Schema {
+                properties: Some(HashMap::from([(
+                    "colors".to_string(),
+                    Schema {
+                        items: Some(Box::new(Schema {
+                            format: Some("enum".to_string()),
+                            r#enum: Some(vec!["RED".to_string(), "BLUE".to_string()]),
+                            ..schema(SchemaType::String)
+                        })),
+                        ..schema(SchemaType::Array)
+                    },
+                )])),
+                required: Some(vec!["colors".to_string()]),
+                ..schema(SchemaType::Object)
+            }),
+        }),
+        safety_settings: Some(vec![SafetySetting {
+            category: HarmCategory::HarmCategoryDangerousContent,
+            threshold: HarmBlockThreshold::BlockOnlyHigh,
+        }]),
+        tools: Some(vec![
+            Tool {
+                function_declarations: Some(vec![FunctionDeclaration {
+                    name: "get_weather".to_string(),
+                    description: "Get the current weather in a city.".to_string(),
+                    parameters: Some(Schema {
+                        properties: Some(HashMap::from([(
+                            "city".to_string(),
+                            Schema {
+                                description: Some("Name of the city.".to_string()),
+                                ..schema(SchemaType::String)
+                            },
+                        )])),
+                        ..schema(SchemaType::Object)
+                    }),
+                }]),
+                code_execution: None,
+            },
+            Tool {
+                function_declarations: None,
+                code_execution: Some(CodeExecution {}),
+            },
+        ]),
+        tool_config: Some(ToolConfig {
+            function_calling_config: FunctionCalingConfig {
+                mode: Some(FunctionCallingMode::Any),
+                allowed_function_names: Some(vec!["get_weather".to_string()]),
+            },
+        }),
+        system_instruction: Some(Content {
+            role: Role::User,
+            parts: vec![Part::from("Answer briefly.")],
+            extra: Default::default(),
+        }),
+        cached_content: Some("cachedContents/abc123".to_string()),
+    };
+
+    assert_eq!(
+        serde_json::to_value(&request).unwrap(),
+        json!({
+            "model": "models/gemini-1.5-flash",
+            "contents": [
+                {
+                    "role": "user",
+                    "parts": [
+                        { "text": "What is in these files?" },
+                        { "inlineData": { "mimeType": "image/png", "data": "iVBORw0KGgo=" } },
+                        { "fileData": { "mimeType": "application/pdf", "uri": "gs://bucket/report.pdf" } }
+                    ]
+                },
+                {
+                    "role": "model",
+                    "parts": [
+                        { "functionCall": { "name": "get_weather", "args": { "city": "Paris" } } },
+                        { "executableCode": { "code": "print(1 + 1)", "language": "PYTHON" } },
+                        { "codeExecutionResult": { "outcome": "OUTCOME_OK", "output": "2\n" } }
+                    ]
+                },
+                {
+                    "role": "user",
+                    "parts": [
+                        { "functionResponse": { "name": "get_weather", "response": { "temperature": 21 } } }
+                    ]
+                }
+            ],
+            "generationConfig": {
+                "candidateCount": 1,
+                "stopSequences": ["END"],
+                "maxOutputTokens": 1024,
+                "temperature": 0.5,
+                "topP": 0.25,
+                "topK": 40,
+                "responseMimeType": "application/json",
+                "responseSchema": {
+                    "type": "OBJECT",
+                    "properties": {
+                        "colors": {
+                            "type": "ARRAY",
+                            "items": { "type": "STRING", "format": "enum", "enum": ["RED", "BLUE"] }
+                        }
+                    },
+                    "required": ["colors"]
+                }
+            },
+            "safetySettings": [
+                { "category": "HARM_CATEGORY_DANGEROUS_CONTENT", "threshold": "BLOCK_ONLY_HIGH" }
+            ],
+            "tools": [
+                {
+                    "functionDeclarations": [
+                        {
+                            "name": "get_weather",
+                            "description": "Get the current weather in a city.",
+                            "parameters": {
+                                "type": "OBJECT",
+                                "properties": {
+                                    "city": { "type": "STRING", "description": "Name of the city." }
+                                }
+                            }
+                        }
+                    ]
+                },
+                { "codeExecution": {} }
+            ],
+            "toolConfig": {
+                "functionCallingConfig": { "mode": "ANY", "allowedFunctionNames": ["get_weather"] }
+            },
+            "systemInstruction": { "role": "user", "parts": [{ "text": "Answer briefly." }] },
+            "cachedContent": "cachedContents/abc123"
+        })
+    );
+
+    // A generation config reads back from the same JSON, e.g. from a configuration file.
+    let json = serde_json::to_value(request.generation_config.unwrap()).unwrap();
+    let generation_config: GenerationConfig = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(generation_config.top_k, Some(40));
+    assert!(matches!(
+        generation_config.response_mime_type,
+        Some(MimeType::ApplicationJson)
+    ));
+    assert_eq!(serde_json::to_value(generation_config).unwrap(), json);
+}
+
+#[test]
+fn test_minimal_generate_content_request() {
+    let request = GenerateContentRequest {
+        model: "models/gemini-1.5-flash".to_string(),
+        contents: vec![Content {
+            role: Role::User,
+            parts: vec![Part::from("Hello")],
+            extra: Default::default(),
+        }],
+        generation_config: Some(GenerationConfig::default()),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        serde_json::to_value(&request).unwrap(),
+        json!({
+            "model": "models/gemini-1.5-flash",
+            "contents": [{ "role": "user", "parts": [{ "text": "Hello" }] }],
+            "generationConfig": {}
+        })
+    );
+}
+
+#[test]
+fn test_enum_values() {
+    let values = |values: Vec<serde_json::Value>| -> Vec<String> {
+        values
+            .into_iter()
+            .map(|value| value.as_str().unwrap().to_string())
+            .collect()
+    };
+
+    assert_eq!(
+        values(vec![
+            json!(MimeType::TextPlain),
+            json!(MimeType::ApplicationJson)
+        ]),
+        ["text/plain", "application/json"]
+    );
+    assert_eq!(
+        values(vec![
+            json!(HarmBlockThreshold::HarmBlockThresholdUnspecified),
+            json!(HarmBlockThreshold::BlockLowAndAbove),
+            json!(HarmBlockThreshold::BlockMediumAndAbove),
+            json!(HarmBlockThreshold::BlockOnlyHigh),
+            json!(HarmBlockThreshold::BlockNone),
+        ]),
+        [
+            "HARM_BLOCK_THRESHOLD_UNSPECIFIED",
+            "BLOCK_LOW_AND_ABOVE",
+            "BLOCK_MEDIUM_AND_ABOVE",
+            "BLOCK_ONLY_HIGH",
+            "BLOCK_NONE"
+        ]
+    );
+    assert_eq!(
+        values(vec![
+            json!(FunctionCallingMode::ModeUnspecified),
+            json!(FunctionCallingMode::Auto),
+            json!(FunctionCallingMode::Any),
+            json!(FunctionCallingMode::None),
+        ]),
+        ["MODE_UNSPECIFIED", "AUTO", "ANY", "NONE"]
+    );
+    assert_eq!(
+        values(vec![
+            json!(SchemaType::String),
+            json!(SchemaType::Number),
+            json!(SchemaType::Integer),
+            json!(SchemaType::Boolean),
+            json!(SchemaType::Array),
+            json!(SchemaType::Object),
+        ]),
+        ["STRING", "NUMBER", "INTEGER", "BOOLEAN", "ARRAY", "OBJECT"]
+    );
+    assert_eq!(
+        values(vec![
+            json!(ExecutableCodeLanguage::LanguageUnspecified),
+            json!(ExecutableCodeLanguage::Python),
+        ]),
+        ["LANGUAGE_UNSPECIFIED", "PYTHON"]
+    );
+    assert_eq!(
+        values(vec![
+            json!(CodeExecutionResultOutcome::Unspecified),
+            json!(CodeExecutionResultOutcome::Ok),
+            json!(CodeExecutionResultOutcome::Failed),
+            json!(CodeExecutionResultOutcome::DeadlineExceeded),
+        ]),
+        [
+            "OUTCOME_UNSPECIFIED",
+            "OUTCOME_OK",
+            "OUTCOME_FAILED",
+            "OUTCOME_DEADLINE_EXCEEDED"
+        ]
+    );
+    assert_eq!(
+        values(vec![
+            json!(TaskType::RetrievalQuery),
+            json!(TaskType::RetrievalDocument)
+        ]),
+        ["RETRIEVAL_QUERY", "RETRIEVAL_DOCUMENT"]
+    );
+}
+
+#[test]
+fn test_count_tokens_and_embed_requests() {
+    let content = Content {
+        role: Role::User,
+        parts: vec![Part::from("Hello")],
+        extra: Default::default(),
+    };
+
+    let request = CountTokensRequest {
+        contents: Some(vec![content.clone()]),
+        generate_content_request: None,
+    };
+    assert_eq!(
+        serde_json::to_value(&request).unwrap(),
+        json!({ "contents": [{ "role": "user", "parts": [{ "text": "Hello" }] }] })
+    );
+
+    let request = CountTokensRequest {
+        contents: None,
+        generate_content_request: Some(GenerateContentRequest {
+            model: "models/gemini-1.5-flash".to_string(),
+            contents: vec![content.clone()],
+            ..Default::default()
+        }),
+    };
+    assert_eq!(
+        serde_json::to_value(&request).unwrap(),
+        json!({
+            "generateContentRequest": {
+                "model": "models/gemini-1.5-flash",
+                "contents": [{ "role": "user", "parts": [{ "text": "Hello" }] }]
+            }
+        })
+    );
+
+    let request = BatchEmbedContentsRequest {
+        requests: vec![EmbedContentRequest {
+            model: "models/text-embedding-004".to_string(),
+            content,
+            task_type: Some(TaskType::RetrievalDocument),
+            title: Some("Greeting".to_string()),
+            output_dimensionality: Some(256),
+        }],
+    };
+    assert_eq!(
+        serde_json::to_value(&request).unwrap(),
+        json!({
+            "requests": [{
+                "model": "models/text-embedding-004",
+                "content": { "role": "user", "parts": [{ "text": "Hello" }] },
+                "taskType": "RETRIEVAL_DOCUMENT",
+                "title": "Greeting",
+                "outputDimensionality": 256
+            }]
+        })
+    );
+}
+
+#[test]
+fn test_cached_content_and_file_metadata() {
+    let cached_content = CachedContent {
+        expiration: ExpireTimeOrTTL::ttl(std::time::Duration::from_secs(300)),
+        display_name: Some("report".to_string()),
+        model: "models/gemini-1.5-flash-001".to_string(),
+        system_instruction: Some(Content {
+            role: Role::User,
+            parts: vec![Part::from("Answer briefly.")],
+            extra: Default::default(),
+        }),
+        contents: vec![Content {
+            role: Role::User,
+            parts: vec![Part::from("A long document.")],
+            extra: Default::default(),
+        }],
+        tool_config: Some(ToolConfig {
+            function_calling_config: FunctionCalingConfig {
+                mode: Some(FunctionCallingMode::None),
+                allowed_function_names: None,
+            },
+        }),
+        ..Default::default()
+    };
+    assert_eq!(
+        serde_json::to_value(&cached_content).unwrap(),
+        json!({
+            "ttl": "300s",
+            "displayName": "report",
+            "model": "models/gemini-1.5-flash-001",
+            "systemInstruction": { "role": "user", "parts": [{ "text": "Answer briefly." }] },
+            "contents": [{ "role": "user", "parts": [{ "text": "A long document." }] }],
+            "toolConfig": { "functionCallingConfig": { "mode": "NONE" } }
+        })
+    );
+
+    let metadata = FileMetadata {
+        display_name: Some("report.pdf".to_string()),
+        mime_type: "application/pdf".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(
+        serde_json::to_value(&metadata).unwrap(),
+        json!({ "displayName": "report.pdf", "mimeType": "application/pdf" })
+    );
+}
+
+#[test]
+fn test_generate_content_response() {
+    let response: GenerateContentResponse = serde_json::from_value(json!({
+        "candidates": [{
+            "index": 0,
+            "content": {
+                "role": "model",
+                "parts": [
+                    { "text": "Let me compute it." },
+                    { "functionCall": { "name": "get_weather", "args": { "city": "Paris" } } },
+                    { "executableCode": { "language": "PYTHON", "code": "print(1 + 1)" } },
+                    { "codeExecutionResult": { "outcome": "OUTCOME_OK", "output": "2\n" } },
+                    { "inlineData": { "mimeType": "image/png", "data": "iVBORw0KGgo=" } }
+                ]
+            },
+            "finishReason": "STOP",
+            "safetyRatings": [
+                { "category": "HARM_CATEGORY_HARASSMENT", "probability": "NEGLIGIBLE" },
+                { "category": "HARM_CATEGORY_HATE_SPEECH", "probability": "LOW", "blocked": false }
+            ],
+            "citationMetadata": {
+                "citationSources": [
+                    { "startIndex": 1, "endIndex": 10, "uri": "https://example.com", "license": "MIT" }
+                ]
+            },
+            "tokenCount": 12
+        }],
+        "promptFeedback": {
+            "blockReason": "SAFETY",
+            "safetyRatings": [{ "category": "HARM_CATEGORY_DANGEROUS_CONTENT", "probability": "HIGH" }]
+        },
+        "usageMetadata": {
+            "promptTokenCount": 10,
+            "candidatesTokenCount": 12,
+            "totalTokenCount": 22,
+            "cachedContentTokenCount": 4
+        }
+    }))
+    .unwrap();
+
+    let candidate = &response.candidates[0];
+    let parts = &candidate.content.as_ref().unwrap().parts;
+    assert_eq!(parts[0].as_text(), Some("Let me compute it."));
+    let function_call = parts[1].as_function_call().unwrap();
+    assert_eq!(function_call.name, "get_weather");
+    assert_eq!(function_call.args["city"], "Paris");
+    assert!(matches!(
+        &parts[2],
+        Part::ExecutableCode {
+            executable_code: ExecutableCode {
+                language: ExecutableCodeLanguage::Python,
+                ..
+            },
+            ..
+        }
+    ));
+    assert!(matches!(
+        &parts[3],
+        Part::CodeExecutionResult {
+            code_execution_result: CodeExecutionResult {
+                outcome: CodeExecutionResultOutcome::Ok,
+                ..
+            },
+            ..
+        }
+    ));
+    assert!(
+        matches!(&parts[4], Part::InlineData { inline_data: blob, .. } if blob.mime_type == "image/png")
+    );
+    assert!(matches!(candidate.finish_reason, Some(FinishReason::Stop)));
+    assert!(matches!(
+        candidate.safety_ratings[1].probability,
+        HarmProbability::Low
+    ));
+    assert_eq!(candidate.safety_ratings[1].blocked, Some(false));
+    let citation = &candidate
+        .citation_metadata
+        .as_ref()
+        .unwrap()
+        .citation_sources[0];
+    assert_eq!(citation.end_index, Some(10));
+    assert_eq!(citation.license.as_deref(), Some("MIT"));
+    assert_eq!(candidate.token_count, Some(12));
+
+    let prompt_feedback = response.prompt_feedback.as_ref().unwrap();
+    assert!(matches!(
+        prompt_feedback.block_reason,
+        Some(BlockReason::Safety)
+    ));
+    assert_eq!(
+        prompt_feedback.safety_ratings[0].category,
+        HarmCategory::HarmCategoryDangerousContent
+    );
+    let usage_metadata = response.usage_metadata.as_ref().unwrap();
+    assert_eq!(usage_metadata.total_token_count, 22);
+    assert_eq!(usage_metadata.cached_content_token_count, Some(4));
+}
+
+#[test]
+fn test_other_responses() {
+    let response: CountTokensResponse =
+        serde_json::from_value(json!({ "totalTokens": 31, "cachedContentTokenCount": 10 }))
+            .unwrap();
+    assert_eq!(response.total_tokens, 31);
+    assert_eq!(response.cached_content_token_count, Some(10));
+
+    let response: EmbedContentResponse =
+        serde_json::from_value(json!({ "embedding": { "values": [0.5, -0.25] } })).unwrap();
+    assert_eq!(response.embedding.values, [0.5, -0.25]);
+
+    let file: File = serde_json::from_value(json!({
+        "name": "files/abc-123",
+        "displayName": "report.pdf",
+        "mimeType": "application/pdf",
+        "sizeBytes": "2048",
+        "createTime": "2024-05-01T10:00:00.000000Z",
+        "updateTime": "2024-05-01T10:00:00.000000Z",
+        "expirationTime": "2024-05-03T10:00:00.000000Z",
+        "sha256Hash": "ZmFrZQ==",
+        "uri": "https://generativelanguage.googleapis.com/v1beta/files/abc-123",
+        "state": "ACTIVE"
+    }))
+    .unwrap();
+    assert_eq!(file.size_bytes, Some(2048));
+    assert_eq!(file.state, FileState::Active);
+    assert_eq!(
+        serde_json::to_value(file.to_part()).unwrap(),
+        json!({
+            "fileData": {
+                "mimeType": "application/pdf",
+                "uri": "https://generativelanguage.googleapis.com/v1beta/files/abc-123"
+            }
+        })
+    );
+}
+
+#[test]
+fn test_unknown_values() {
+    let response: GenerateContentResponse = serde_json::from_value(json!({
+        "candidates": [{
+            "index": 0,
+            "content": { "role": "model", "parts": [{ "text": "Hi" }] },
+            "finishReason": "SOME_FUTURE_REASON",
+            "safetyRatings": [
+                { "category": "HARM_CATEGORY_CIVIC_INTEGRITY", "probability": "NEGLIGIBLE" },
+                { "category": "HARM_CATEGORY_SOME_FUTURE_HARM", "probability": "VERY_HIGH", "severity": "HARM_SEVERITY_LOW" }
+            ],
+            "avgLogprobs": -0.5
+        }],
+        "promptFeedback": { "blockReason": "PROHIBITED_CONTENT", "safetyRatings": [] },
+        "usageMetadata": {
+            "promptTokenCount": 1,
+            "candidatesTokenCount": 1,
+            "totalTokenCount": 2,
+            "thoughtsTokenCount": 7
+        },
+        "modelVersion": "gemini-1.5-flash-002"
+    }))
+    .unwrap();
+
+    let candidate = &response.candidates[0];
+    assert!(
+        matches!(&candidate.finish_reason, Some(FinishReason::Unknown(reason)) if reason == "SOME_FUTURE_REASON")
+    );
+    assert_eq!(
+        candidate.safety_ratings[0].category,
+        HarmCategory::HarmCategoryCivicIntegrity
+    );
+    assert_eq!(
+        candidate.safety_ratings[1].category,
+        HarmCategory::Unknown("HARM_CATEGORY_SOME_FUTURE_HARM".to_string())
+    );
+    assert!(matches!(
+        &candidate.safety_ratings[1].probability,
+        HarmProbability::Unknown(probability) if probability == "VERY_HIGH"
+    ));
+    assert_eq!(
+        candidate.safety_ratings[1].extra["severity"],
+        "HARM_SEVERITY_LOW"
+    );
+    assert_eq!(candidate.extra["avgLogprobs"], -0.5);
+    assert!(matches!(
+        response.prompt_feedback.as_ref().unwrap().block_reason,
+        Some(BlockReason::ProhibitedContent)
+    ));
+    assert_eq!(
+        response.usage_metadata.as_ref().unwrap().extra["thoughtsTokenCount"],
+        7
+    );
+    assert_eq!(response.extra["modelVersion"], "gemini-1.5-flash-002");
+
+    // Unknown values and fields are serialized back as received.
+    let value = serde_json::to_value(&response).unwrap();
+    assert_eq!(value["modelVersion"], "gemini-1.5-flash-002");
+    assert_eq!(value["candidates"][0]["finishReason"], "SOME_FUTURE_REASON");
+    assert_eq!(
+        value["candidates"][0]["safetyRatings"][1],
+        json!({
+            "category": "HARM_CATEGORY_SOME_FUTURE_HARM",
+            "probability": "VERY_HIGH",
+            "blocked": null,
+            "severity": "HARM_SEVERITY_LOW"
+        })
+    );
+
+    let role: Role = serde_json::from_value(json!("function")).unwrap();
+    assert!(matches!(&role, Role::Unknown(role) if role == "function"));
+    assert_eq!(serde_json::to_value(&role).unwrap(), "function");
+    let threshold: HarmBlockThreshold = serde_json::from_value(json!("OFF")).unwrap();
+    assert!(matches!(threshold, HarmBlockThreshold::Off));
+    let state: FileState = serde_json::from_value(json!("ARCHIVED")).unwrap();
+    assert_eq!(state, FileState::Unknown("ARCHIVED".to_string()));
+    assert_eq!(
+        serde_json::to_value(SchemaType::Unknown("NULL".to_string())).unwrap(),
+        "NULL"
+    );
+}
+
+#[test]
+fn test_blocked_prompt_response() {
+    let response: GenerateContentResponse = serde_json::from_value(json!({
+        "promptFeedback": {
+            "blockReason": "SAFETY",
+            "safetyRatings": [
+                { "category": "HARM_CATEGORY_HARASSMENT", "probability": "HIGH", "blocked": true }
+            ]
+        },
+        "usageMetadata": { "promptTokenCount": 8, "totalTokenCount": 8 }
+    }))
+    .unwrap();
+
+    assert!(response.candidates.is_empty());
+    let prompt_feedback = response.prompt_feedback.as_ref().unwrap();
+    assert!(matches!(
+        prompt_feedback.block_reason,
+        Some(BlockReason::Safety)
+    ));
+    assert_eq!(prompt_feedback.safety_ratings[0].blocked, Some(true));
+    let usage_metadata = response.usage_metadata.as_ref().unwrap();
+    assert_eq!(usage_metadata.prompt_token_count, 8);
+    assert_eq!(usage_metadata.candidates_token_count, 0);
+}
+
+#[test]
+fn test_stopped_candidate_responses() {
+    // A candidate stopped for safety has no content.
+    let response: GenerateContentResponse = serde_json::from_value(json!({
+        "candidates": [{
+            "finishReason": "SAFETY",
+            "index": 0,
+            "safetyRatings": [
+                { "category": "HARM_CATEGORY_SEXUALLY_EXPLICIT", "probability": "NEGLIGIBLE" },
+                { "category": "HARM_CATEGORY_DANGEROUS_CONTENT", "probability": "HIGH", "blocked": true }
+            ]
+        }],
+        "usageMetadata": { "promptTokenCount": 12, "totalTokenCount": 12 }
+    }))
+    .unwrap();
+    let candidate = &response.candidates[0];
+    assert!(matches!(
+        candidate.finish_reason,
+        Some(FinishReason::Safety)
+    ));
+    assert!(candidate.content.is_none());
+    assert_eq!(candidate.safety_ratings.len(), 2);
+
+    // A candidate stopped for recitation has neither parts nor safety ratings, and the first index is omitted.
+    let response: GenerateContentResponse = serde_json::from_value(json!({
+        "candidates": [{
+            "content": { "role": "model" },
+            "finishReason": "RECITATION",
+            "citationMetadata": {
+                "citationSources": [{ "startIndex": 0, "endIndex": 120, "uri": "https://example.com/lyrics" }]
+            }
+        }]
+    }))
+    .unwrap();
+    let candidate = &response.candidates[0];
+    assert_eq!(candidate.index, 0);
+    assert!(matches!(
+        candidate.finish_reason,
+        Some(FinishReason::Recitation)
+    ));
+    assert!(candidate.content.as_ref().unwrap().parts.is_empty());
+    assert!(candidate.safety_ratings.is_empty());
+    assert!(response.usage_metadata.is_none());
+}
+
+#[test]
+fn test_empty_chunk_response() {
+    let response: GenerateContentResponse = serde_json::from_value(json!({})).unwrap();
+    assert!(response.candidates.is_empty());
+    assert!(response.prompt_feedback.is_none());
+    assert!(response.usage_metadata.is_none());
+
+    let response: GenerateContentResponse = serde_json::from_value(json!({
+        "promptFeedback": { "safetyRatings": [] }
+    }))
+    .unwrap();
+    assert!(response
+        .prompt_feedback
+        .as_ref()
+        .unwrap()
+        .block_reason
+        .is_none());
+}
+
+#[test]
+fn test_parts() {
+    let parts: Vec<Part> = serde_json::from_value(json!([
+        { "text": "The answer is 42." },
+        { "text": "The user asks about life.", "thought": true },
+        { "inlineData": { "mimeType": "image/png", "data": "iVBORw0KGgo=" } },
+        { "fileData": { "mimeType": "video/mp4", "uri": "gs://bucket/video.mp4" } },
+        { "functionResponse": { "name": "lookup", "response": { "found": true } } },
+        { "videoMetadata": { "startOffset": "10s" } }
+    ]))
+    .unwrap();
+
+    assert_eq!(parts[0].as_text(), Some("The answer is 42."));
+    assert!(matches!(&parts[1], Part::Thought { text, .. } if text == "The user asks about life."));
+    assert_eq!(parts[1].as_text(), None);
+    assert!(
+        matches!(&parts[2], Part::InlineData { inline_data: blob, .. } if blob.data == "iVBORw0KGgo=")
+    );
+    assert!(
+        matches!(&parts[3], Part::FileData { file_data, .. } if file_data.mime_type == "video/mp4")
+    );
+    assert!(
+        matches!(&parts[4], Part::FunctionResponse { function_response: response, .. } if response.name == "lookup")
+    );
+    assert!(
+        matches!(&parts[5], Part::Unknown(unknown) if unknown.fields()["videoMetadata"]["startOffset"] == "10s")
+    );
+
+    // Every kind of part is serialized back as received.
+    assert_eq!(
+        serde_json::to_value(&parts).unwrap(),
+        json!([
+            { "text": "The answer is 42." },
+            { "text": "The user asks about life.", "thought": true },
+            { "inlineData": { "mimeType": "image/png", "data": "iVBORw0KGgo=" } },
+            { "fileData": { "mimeType": "video/mp4", "uri": "gs://bucket/video.mp4" } },
+            { "functionResponse": { "name": "lookup", "response": { "found": true } } },
+            { "videoMetadata": { "startOffset": "10s" } }
+        ])
+    );
+
+    assert_eq!(
+        serde_json::to_value(Part::image_bytes("image/png", b"\x89PNG")).unwrap(),
+        json!({ "inlineData": { "mimeType": "image/png", "data": "iVBORw==" } })
+    );
+    assert_eq!(
+        serde_json::to_value(Part::from(String::from("Hello"))).unwrap(),
+        json!({ "text": "Hello" })
+    );
+    assert_eq!(
+        serde_json::to_value(Content::from("Hello")).unwrap(),
+        json!({ "role": "user", "parts": [{ "text": "Hello" }] })
+    );
+}
+
+#[test]
+fn test_part_unknown_fields() {
+    let parts = json!([
+        { "text": "Let me check.", "thoughtSignature": "c2lnbmF0dXJl" },
+        {
+            "functionCall": { "name": "get_weather", "args": { "city": "Paris" } },
+            "thoughtSignature": "c2lnbmF0dXJl"
+        },
+        {
+            "fileData": { "mimeType": "video/mp4", "uri": "gs://bucket/video.mp4" },
+            "videoMetadata": { "startOffset": "10s" }
+        }
+    ]);
+    let decoded: Vec<Part> = serde_json::from_value(parts.clone()).unwrap();
+
+    assert_eq!(decoded[0].as_text(), Some("Let me check."));
+    assert_eq!(decoded[0].extra()["thoughtSignature"], "c2lnbmF0dXJl");
+    assert_eq!(decoded[1].as_function_call().unwrap().name, "get_weather");
+    assert_eq!(decoded[1].extra()["thoughtSignature"], "c2lnbmF0dXJl");
+    assert!(matches!(decoded[2], Part::FileData { .. }));
+
+    // The unknown fields are sent back, e.g. when replaying a chat history.
+    assert_eq!(serde_json::to_value(&decoded).unwrap(), parts);
+}
+
+#[test]
+fn test_payload_unknown_fields() {
+    let content = json!({
+        "role": "model",
+        "parts": [
+            { "functionCall": { "id": "call-1", "name": "get_weather", "args": { "city": "Paris" } } },
+            { "functionResponse": { "id": "call-1", "name": "get_weather", "response": { "temperature": 21 } } },
+            { "inlineData": { "mimeType": "image/png", "data": "iVBORw0KGgo=", "displayName": "chart.png" } },
+            { "fileData": { "mimeType": "video/mp4", "uri": "gs://bucket/video.mp4", "displayName": "video.mp4" } },
+            { "executableCode": { "code": "print(1 + 1)", "language": "PYTHON", "id": "code-1" } },
+            { "codeExecutionResult": { "outcome": "OUTCOME_OK", "output": "2\n", "id": "code-1" } }
+        ],
+        "turnId": "turn-1"
+    });
+    let decoded: Content = serde_json::from_value(content.clone()).unwrap();
+
+    assert_eq!(decoded.extra["turnId"], "turn-1");
+    let function_call = decoded.parts[0].as_function_call().unwrap();
+    assert_eq!(function_call.extra["id"], "call-1");
+
+    // The unknown fields are sent back, e.g. the id matching a function response to its call.
+    assert_eq!(serde_json::to_value(&decoded).unwrap(), content);
+}
@@ -1,27 +1,32 @@
 use std::collections::HashMap;
 
+use base64::prelude::{Engine, BASE64_STANDARD};
+
 use super::schema::Schema as FunctionDeclarationSchema;
 
 /// Tool details that the model may use to generate response.
 ///
 /// A `Tool` is a piece of code that enables the system to interact with external systems
 /// to perform an action, or set of actions, outside of knowledge and scope of the model.
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Tool {
     /// A list of [FunctionDeclaration] available to the model that can be used for function calling.
     /// The model or system does not execute the function.
     /// Instead the defined function may be returned as a [FunctionCall] part with arguments to the client side for execution.
     /// The next conversation turn may contain a [FunctionResponse] with the role "function" generation context for the next model turn.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub function_declarations: Option<Vec<FunctionDeclaration>>,
 
     /// Enables the model to execute code as part of generation.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub code_execution: Option<CodeExecution>,
 }
 
 /// FunctionDeclaration is structured representation of a function declaration as defined by the [OpenAPI 3.03 specification](https://spec.openapis.org/oas/v3.0.3).
 /// Include in this declaration are the function name and parameters.
 /// This [FunctionDeclaration] is a representation of a block of code that can be used as a `Tool` by the model and executed by the client.
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct FunctionDeclaration {
     /// The name of the function.
     /// Must be a-z, A-Z, 0-9, or contain underscores and dashes, with a maximum length of 63.
@@ -34,26 +39,30 @@ pub struct FunctionDeclaration {
     /// Reflects the Open API 3.03 Parameter Object
     /// - { string } Key: the name of the parameter. Parameter names are case sensitive.
     /// - { Schema } Value: the Schema defining the type used for the parameter.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub parameters: Option<FunctionDeclarationSchema>,
 }
 
 /// [CodeExecution] is tool that executes code generated by the model, and automatically returns the result to the model.
 ///
 /// See also [ExecutableCode] and [CodeExecutionResult] which are only generated when using this tool.
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct CodeExecution;
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CodeExecution {}
 
 /// [ToolConfig] is the Tool configuration containing parameters for specifying [Tool] use in the request.
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ToolConfig {
     pub function_calling_config: FunctionCalingConfig,
 }
 
 /// FunctionCallingConfig holds configuration for function calling.
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct FunctionCalingConfig {
     /// Specifies the mode in which function calling should execute.
     /// If unspecified, the default value will be set to AUTO.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub mode: Option<FunctionCallingMode>,
 
     /// A set of function names that, when provided, limits the functions the model will call.
@@ -61,24 +70,33 @@ pub struct FunctionCalingConfig {
     /// This should only be set when the Mode is ANY.
     /// Function names should match [FunctionDeclaration::name].
     /// With mode set to ANY, model will predict a function call from the set of function names provided.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub allowed_function_names: Option<Vec<String>>,
 }
 /// FunctionCallingMode is defines the execution behavior for function calling by defining the execution mode.
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum FunctionCallingMode {
     /// Unspecified function calling mode. This value should not be used.
+    #[serde(rename = "MODE_UNSPECIFIED")]
     ModeUnspecified,
 
     /// Default model behavior, model decides to predict either a function call or a natural language repspose.
+    #[serde(rename = "AUTO")]
     Auto,
 
     /// Model is constrained to always predicting a function call only.
     /// If [allowed_function_names][FunctionCalingConfig::allowed_function_names] are set, the predicted function call will be limited to any one of allowed_function_names,
     /// else the predicted function call will be any one of the provided [function_declarations][Tool::function_declarations].
+    #[serde(rename = "ANY")]
     Any,
 
     /// Model will not predict any function call. Model behavior is same as when not passing any function declarations.
+    #[serde(rename = "NONE")]
     None,
+
+    /// A value unknown to this version of the SDK, kept as is.
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 /// The base structured datatype containing multi-part content of a message.
@@ -91,7 +109,11 @@ pub struct Content {
     pub role: Role,
 
     /// Ordered `Parts` that constitute a single message. Parts may have different MIME types.
+    #[serde(default)]
     pub parts: Vec<Part>,
+    /// Fields unknown to this version of the SDK, kept as is.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -100,34 +122,430 @@ pub enum Role {
     User,
     #[serde(rename = "model")]
     Model,
+
+    /// A value unknown to this version of the SDK, kept as is.
+    #[serde(untagged)]
+    Unknown(String),
 }
 
-/// A Part is a piece of model content.
-#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
-pub struct Part {
+impl From<Vec<Part>> for Content {
+    /// A user turn made of the given parts.
+    fn from(parts: Vec<Part>) -> Self {
+        Self {
+            role: Role::User,
+            parts,
+            extra: Default::default(),
+        }
+    }
+}
+
+impl From<Part> for Content {
+    fn from(part: Part) -> Self {
+        vec![part].into()
+    }
+}
+
+impl From<&str> for Content {
+    fn from(text: &str) -> Self {
+        Part::from(text).into()
+    }
+}
+
+impl From<String> for Content {
+    fn from(text: String) -> Self {
+        Part::from(text).into()
+    }
+}
+
+/// A Part is a piece of model content. Each part holds exactly one kind of data.
+///
+/// Every variant keeps the fields of the part unknown to this version of the SDK in its `extra` map,
+/// e.g. a thought signature to send back to the model.
+///
+/// ```
+/// use google_generative_ai_rs::v1::types::content_types::Part;
+///
+/// let parts = vec![
+///     Part::from("What is in this image?"),
+///     Part::image_bytes("image/png", [0x89, b'P', b'N', b'G']),
+/// ];
+/// assert_eq!(parts[0].as_text(), Some("What is in this image?"));
+/// ```
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(from = "PartFields", into = "PartFields")]
+pub enum Part {
+    /// Inline text.
+    Text {
+        text: String,
+        extra: serde_json::Map<String, serde_json::Value>,
+    },
+
+    /// Inline media bytes.
+    InlineData {
+        inline_data: GenerativeContentBlob,
+        extra: serde_json::Map<String, serde_json::Value>,
+    },
+
+    /// URI based data, such as an uploaded file.
+    FileData {
+        file_data: FileData,
+        extra: serde_json::Map<String, serde_json::Value>,
+    },
+
+    /// A function call predicted by the model.
+    FunctionCall {
+        function_call: FunctionCall,
+        extra: serde_json::Map<String, serde_json::Value>,
+    },
+
+    /// The result of a [Part::FunctionCall], sent back to the model.
+    FunctionResponse {
+        function_response: FunctionResponse,
+        extra: serde_json::Map<String, serde_json::Value>,
+    },
+
+    /// Code generated by the model for the [CodeExecution] tool.
+    ExecutableCode {
+        executable_code: ExecutableCode,
+        extra: serde_json::Map<String, serde_json::Value>,
+    },
+
+    /// The result of running an [ExecutableCode].
+    CodeExecutionResult {
+        code_execution_result: CodeExecutionResult,
+        extra: serde_json::Map<String, serde_json::Value>,
+    },
+
+    /// A summary of the model's reasoning, sent as text with `"thought": true`.
+    Thought {
+        text: String,
+        extra: serde_json::Map<String, serde_json::Value>,
+    },
+
+    /// A kind of part unknown to this version of the SDK, only obtained from a response.
+    Unknown(UnknownPart),
+}
+
+/// UnknownPart is a [Part] of a kind unknown to this version of the SDK.
+///
+/// It cannot be built, only deserialized from a response. Its fields are kept as is,
+/// so it is sent back unchanged, e.g. as part of a chat history.
+#[derive(Debug, Clone)]
+pub struct UnknownPart {
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl UnknownPart {
+    /// The fields of the part.
+    pub fn fields(&self) -> &serde_json::Map<String, serde_json::Value> {
+        &self.fields
+    }
+}
+
+impl Part {
+    /// Inline media, e.g. an image, audio, video or a PDF, from its raw bytes which are base64 encoded.
+    ///
+    /// See [media][crate::v1::media] to detect the MIME type and check the inline size limit.
+    pub fn inline_data(mime_type: impl Into<String>, bytes: impl AsRef<[u8]>) -> Self {
+        Self::InlineData {
+            inline_data: GenerativeContentBlob {
+                mime_type: mime_type.into(),
+                data: BASE64_STANDARD.encode(bytes),
+                extra: Default::default(),
+            },
+            extra: Default::default(),
+        }
+    }
+
+    /// Inline image from its raw bytes, same as [Part::inline_data].
+    pub fn image_bytes(mime_type: impl Into<String>, bytes: impl AsRef<[u8]>) -> Self {
+        Self::inline_data(mime_type, bytes)
+    }
+
+    /// A file referenced by its URI, e.g. an uploaded file or a `gs://` URI.
+    pub fn file_data(mime_type: impl Into<String>, uri: impl Into<String>) -> Self {
+        FileData {
+            mime_type: mime_type.into(),
+            uri: uri.into(),
+            extra: Default::default(),
+        }
+        .into()
+    }
+
+    /// The response of a function called by the model.
+    pub fn function_response(
+        name: impl Into<String>,
+        response: HashMap<String, serde_json::Value>,
+    ) -> Self {
+        FunctionResponse {
+            name: name.into(),
+            response,
+            extra: Default::default(),
+        }
+        .into()
+    }
+
+    /// The text of a [Part::Text].
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::Text { text, .. } => Some(text),
+            _ => None,
+        }
+    }
+
+    /// The function call of a [Part::FunctionCall].
+    pub fn as_function_call(&self) -> Option<&FunctionCall> {
+        match self {
+            Self::FunctionCall { function_call, .. } => Some(function_call),
+            _ => None,
+        }
+    }
+
+    /// The fields of the part unknown to this version of the SDK, or all the fields of a [Part::Unknown].
+    pub fn extra(&self) -> &serde_json::Map<String, serde_json::Value> {
+        match self {
+            Self::Text { extra, .. }
+            | Self::InlineData { extra, .. }
+            | Self::FileData { extra, .. }
+            | Self::FunctionCall { extra, .. }
+            | Self::FunctionResponse { extra, .. }
+            | Self::ExecutableCode { extra, .. }
+            | Self::CodeExecutionResult { extra, .. }
+            | Self::Thought { extra, .. } => extra,
+            Self::Unknown(unknown) => unknown.fields(),
+        }
+    }
+}
+
+impl From<&str> for Part {
+    fn from(text: &str) -> Self {
+        text.to_string().into()
+    }
+}
+
+impl From<String> for Part {
+    fn from(text: String) -> Self {
+        Self::Text {
+            text,
+            extra: Default::default(),
+        }
+    }
+}
+
+impl From<GenerativeContentBlob> for Part {
+    fn from(inline_data: GenerativeContentBlob) -> Self {
+        Self::InlineData {
+            inline_data,
+            extra: Default::default(),
+        }
+    }
+}
+
+impl From<FileData> for Part {
+    fn from(file_data: FileData) -> Self {
+        Self::FileData {
+            file_data,
+            extra: Default::default(),
+        }
+    }
+}
+
+impl From<FunctionCall> for Part {
+    fn from(function_call: FunctionCall) -> Self {
+        Self::FunctionCall {
+            function_call,
+            extra: Default::default(),
+        }
+    }
+}
+
+impl From<FunctionResponse> for Part {
+    fn from(function_response: FunctionResponse) -> Self {
+        Self::FunctionResponse {
+            function_response,
+            extra: Default::default(),
+        }
+    }
+}
+
+impl From<ExecutableCode> for Part {
+    fn from(executable_code: ExecutableCode) -> Self {
+        Self::ExecutableCode {
+            executable_code,
+            extra: Default::default(),
+        }
+    }
+}
+
+impl From<CodeExecutionResult> for Part {
+    fn from(code_execution_result: CodeExecutionResult) -> Self {
+        Self::CodeExecutionResult {
+            code_execution_result,
+            extra: Default::default(),
+        }
+    }
+}
+
+/// PartFields is the JSON representation of a [Part], in which the kind of data is given by the field which is set.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PartFields {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub text: Option<String>,
+    thought: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub inline_data: Option<GenerativeContentBlob>,
+    inline_data: Option<GenerativeContentBlob>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub function_call: Option<FunctionCall>,
+    function_call: Option<FunctionCall>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub function_response: Option<FunctionResponse>,
+    function_response: Option<FunctionResponse>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub file_data: Option<FileData>,
+    file_data: Option<FileData>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub executable_code: Option<ExecutableCode>,
+    executable_code: Option<ExecutableCode>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub code_execution_result: Option<CodeExecutionResult>,
+    code_execution_result: Option<CodeExecutionResult>,
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl From<PartFields> for Part {
+    fn from(fields: PartFields) -> Self {
+        match fields {
+            PartFields {
+                text: Some(text),
+                thought: Some(true),
+                extra,
+                ..
+            } => Self::Thought { text, extra },
+            PartFields {
+                text: Some(text),
+                extra,
+                ..
+            } => Self::Text { text, extra },
+            PartFields {
+                inline_data: Some(inline_data),
+                extra,
+                ..
+            } => Self::InlineData { inline_data, extra },
+            PartFields {
+                file_data: Some(file_data),
+                extra,
+                ..
+            } => Self::FileData { file_data, extra },
+            PartFields {
+                function_call: Some(function_call),
+                extra,
+                ..
+            } => Self::FunctionCall {
+                function_call,
+                extra,
+            },
+            PartFields {
+                function_response: Some(function_response),
+                extra,
+                ..
+            } => Self::FunctionResponse {
+                function_response,
+                extra,
+            },
+            PartFields {
+                executable_code: Some(executable_code),
+                extra,
+                ..
+            } => Self::ExecutableCode {
+                executable_code,
+                extra,
+            },
+            PartFields {
+                code_execution_result: Some(code_execution_result),
+                extra,
+                ..
+            } => Self::CodeExecutionResult {
+                code_execution_result,
+                extra,
+            },
+            PartFields { extra, .. } => Self::Unknown(UnknownPart { fields: extra }),
+        }
+    }
+}
+
+impl From<Part> for PartFields {
+    fn from(part: Part) -> Self {
+        match part {
+            Part::Text { text, extra } => Self {
+                text: Some(text),
+                extra,
+                ..Default::default()
+            },
+            Part::Thought { text, extra } => Self {
+                text: Some(text),
+                thought: Some(true),
+                extra,
+                ..Default::default()
+            },
+            Part::InlineData { inline_data, extra } => Self {
+                inline_data: Some(inline_data),
+                extra,
+                ..Default::default()
+            },
+            Part::FileData { file_data, extra } => Self {
+                file_data: Some(file_data),
+                extra,
+                ..Default::default()
+            },
+            Part::FunctionCall {
+                function_call,
+                extra,
+            } => Self {
+                function_call: Some(function_call),
+                extra,
+                ..Default::default()
+            },
+            Part::FunctionResponse {
+                function_response,
+                extra,
+            } => Self {
+                function_response: Some(function_response),
+                extra,
+                ..Default::default()
+            },
+            Part::ExecutableCode {
+                executable_code,
+                extra,
+            } => Self {
+                executable_code: Some(executable_code),
+                extra,
+                ..Default::default()
+            },
+            Part::CodeExecutionResult {
+                code_execution_result,
+                extra,
+            } => Self {
+                code_execution_result: Some(code_execution_result),
+                extra,
+                ..Default::default()
+            },
+            Part::Unknown(UnknownPart { fields }) => Self {
+                extra: fields,
+                ..Default::default()
+            },
+        }
+    }
 }
 
 /// Interface for sending an image.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct GenerativeContentBlob {
     pub mime_type: String,
 
     /// Image as a base64 string.
     pub data: String,
+    /// Fields unknown to this version of the SDK, kept as is.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// FunctionCall is a predicted [FunctionCall] returned from the model that contains a string representing the [FunctionDeclaration::name] with the arguments and their values.
@@ -139,6 +557,9 @@ pub struct FunctionCall {
 
     /// The function parameters and values in JSON object format.
     pub args: HashMap<String, serde_json::Value>,
+    /// Fields unknown to this version of the SDK, kept as is, e.g. the `id` of the call.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// FunctionResponse is the result output from a [FunctionCall] that contains a string representing the [FunctionDeclaration::name] and a structured JSON object containing any output from the function is used as context to the model.
@@ -151,14 +572,24 @@ pub struct FunctionResponse {
 
     /// The function response in JSON object format.
     pub response: HashMap<String, serde_json::Value>,
+    /// Fields unknown to this version of the SDK, kept as is.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// FileData is URI based data.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct FileData {
     /// The IANA standard MIME type of the source data.
     pub mime_type: String,
+
+    /// The URI of the data: the [File::uri][crate::v1::types::server::files::File::uri] of an uploaded file
+    /// on the Gemini Developer API, or a Cloud Storage URI such as `gs://bucket/image.png` on Vertex AI.
     pub uri: String,
+    /// Fields unknown to this version of the SDK, kept as is.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// ExecutableCode is code generated by the model that is meant to be executed, and the result returned to the model.
@@ -171,13 +602,22 @@ pub struct ExecutableCode {
 
     /// Programming language of the [code][ExecutableCode::code].
     pub language: ExecutableCodeLanguage,
+    /// Fields unknown to this version of the SDK, kept as is.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// ExecutableCodeLanguage is supported programming languages for the generated code.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum ExecutableCodeLanguage {
+    #[serde(rename = "LANGUAGE_UNSPECIFIED")]
     LanguageUnspecified,
+    #[serde(rename = "PYTHON")]
     Python,
+
+    /// A value unknown to this version of the SDK, kept as is.
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 /// CodeExecutionResult is result of executing the [ExecutableCode].
@@ -189,22 +629,34 @@ pub struct CodeExecutionResult {
     pub outcome: CodeExecutionResultOutcome,
 
     /// Contains stdout when code execution is successful, stderr or other description otherwise.
+    #[serde(default)]
     pub output: String,
+    /// Fields unknown to this version of the SDK, kept as is.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// CodeExecutionResultOutcome is enumeration of possible outcomes of the code execution.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum CodeExecutionResultOutcome {
     /// Unspecified status. This value should not be used.
+    #[serde(rename = "OUTCOME_UNSPECIFIED")]
     Unspecified,
 
     /// Code execution completed successfully.
+    #[serde(rename = "OUTCOME_OK")]
     Ok,
 
     /// Code execution finished but with a failure. `stderr` should contain the reason.
+    #[serde(rename = "OUTCOME_FAILED")]
     Failed,
 
     /// Code execution ran for too long, and was cancelled.
     /// There may or may not be a partial output present.
+    #[serde(rename = "OUTCOME_DEADLINE_EXCEEDED")]
     DeadlineExceeded,
+
+    /// A value unknown to this version of the SDK, kept as is.
+    #[serde(untagged)]
+    Unknown(String),
 }
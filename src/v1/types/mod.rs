@@ -1,3 +1,5 @@
+#[cfg(test)]
+mod conformance;
 pub mod content_types;
 pub mod generation_types;
 pub mod model;
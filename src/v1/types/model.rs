@@ -25,8 +25,9 @@ pub struct ModelParams {
     /// The model will adhere the instructions more strongly than if they appeared in a normal prompt.
     pub system_instruction: Option<Content>,
 
-    /// The name of the CachedContent to use.
-    /// Must have already been created with [Client.CreateCachedContent].
+    /// The CachedContent to use, either as returned by the [CacheManager][crate::v1::server::cache_manager::CacheManager]
+    /// or created from its name, e.g. `"cachedContents/abc123".into()`.
+    /// Must have already been created with [CacheManager::create][crate::v1::server::cache_manager::CacheManager::create].
     pub cached_content: Option<CachedContent>,
 }
 
@@ -44,12 +45,12 @@ impl ModelParams {
     }
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Model {
     /// The resource name of the `Model`.
     ///
-    /// Format: `models/{model}` with a `{model}` naming convention of:
+    /// Format: `models/{model}`, or `publishers/google/models/{model}` on Vertex AI, with a `{model}` naming convention of:
     /// - "{base_model_id}-{version}"
     ///
     /// Examples:
@@ -64,24 +65,31 @@ pub struct Model {
 
     /// The version number of the model.
     /// This represents the major version
+    #[serde(default, alias = "versionId")]
     pub version: String,
 
     /// The human-readable name of the model. E.g. "Chat Bison".
     /// The name can be up to 128 characters long and can consist of any UTF-8 characters.
+    /// Not returned by Vertex AI.
+    #[serde(default)]
     pub display_name: String,
 
-    /// A short description of the model.
+    /// A short description of the model. Not returned by Vertex AI.
+    #[serde(default)]
     pub description: String,
 
-    /// Maximum number of input tokens allowed for this model.
+    /// Maximum number of input tokens allowed for this model. Not returned by Vertex AI.
+    #[serde(default)]
     pub input_token_limit: u32,
 
-    /// Maximum number of output tokens available for this model.
+    /// Maximum number of output tokens available for this model. Not returned by Vertex AI.
+    #[serde(default)]
     pub output_token_limit: u32,
 
-    /// The model's supported generation methods.
+    /// The model's supported generation methods. Not returned by Vertex AI.
     ///
     /// The method names are defined as Pascal case strings, such as `generateMessage` which correspond to API methods.
+    #[serde(default)]
     pub supported_generation_methods: Vec<String>,
 
     /// Controls the randomness of the output.
@@ -106,4 +114,8 @@ pub struct Model {
     /// This value specifies default to be used by the backend while making the call to the model.
     /// If empty, indicates the model doesn't use top-k sampling, and `top_k` isn't allowed as a generation parameter.
     pub top_k: Option<u32>,
+
+    /// Fields unknown to this version of the SDK, kept as is.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
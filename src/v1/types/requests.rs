@@ -1,18 +1,18 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 
 use crate::v1::traits::Stringify;
 
 use super::{
-    content_types::{Content, Tool, ToolConfig},
+    content_types::{Content, Part, Tool, ToolConfig},
     generation_types::GenerationConfig,
     safety_types::SafetySetting,
-    server::caching::CachedContent,
 };
 
-/// Params passed to getGenerativeModel() or GoogleAIFileManager().
+/// Params passed to [get_generative_model][crate::v1::genai::GoogleGenerativeAI::get_generative_model] or [get_file_manager][crate::v1::genai::GoogleGenerativeAI::get_file_manager].
 #[derive(Debug)]
 pub struct RequestOptions {
     /// Request timeout in milliseconds.
+    /// For streamed responses, this bounds the wait for the first byte and between two chunks instead of the whole stream.
     pub timeout: Option<u64>,
 
     /// Version of API endpoint to call (e.g. "v1" or "v1beta").
@@ -23,11 +23,54 @@ pub struct RequestOptions {
     /// Used by wrapper SDKs.
     pub api_client: Option<String>,
 
-    /// Base endpoint url. Defaults to "https://generativelanguage.googleapis.com"
+    /// Base endpoint url. Defaults to <https://generativelanguage.googleapis.com>,
+    /// or `https://{location}-aiplatform.googleapis.com` on [Backend::VertexAI].
     pub base_url: Option<String>,
 
-    /// Custom HTTP request headers.
+    /// Custom HTTP request headers, sent with every request including uploads.
+    /// They replace the default headers of the same name, such as x-goog-api-client or content-type.
     pub custom_headers: Option<HashMap<String, String>>,
+
+    /// Policy to retry requests failing with a transient error.
+    /// If not specified, every request is attempted exactly once.
+    pub retry_policy: Option<RetryPolicy>,
+}
+
+/// RetryPolicy configures how requests failing with a transient error are retried.
+///
+/// The delay between attempts grows exponentially from `base_delay` up to `max_delay`,
+/// unless the server asks for a specific delay through a `RetryInfo` error detail or a `Retry-After` header.
+/// Chunks of a resumable upload and streamed response bodies are never retried.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one.
+    pub max_attempts: u32,
+
+    /// Delay before the first retry.
+    pub base_delay: Duration,
+
+    /// Maximum delay between two attempts.
+    /// A request is not retried if the server asks to wait longer than this.
+    pub max_delay: Duration,
+
+    /// Fraction of the delay randomly added or removed, between `0.0` (no jitter) and `1.0`.
+    pub jitter: f64,
+
+    /// HTTP status codes which are retried.
+    /// Connection errors and timeouts are always retried.
+    pub retryable_status_codes: Vec<u16>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            jitter: 0.2,
+            retryable_status_codes: vec![429, 500, 503, 504],
+        }
+    }
 }
 
 #[derive(Debug, Default, Clone)]
@@ -46,20 +89,108 @@ impl Stringify for ApiVersion {
     }
 }
 
+/// Backend is the API serving the requests of [GoogleGenerativeAI][crate::v1::genai::GoogleGenerativeAI].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum Backend {
+    /// The Gemini Developer API, authenticated with an API key.
+    #[default]
+    GeminiDeveloperApi,
+
+    /// Vertex AI in a Google Cloud project, authenticated with an OAuth2 token,
+    /// e.g. a [ServiceAccount][crate::v1::auth::ServiceAccount] or a [BearerToken][crate::v1::auth::BearerToken].
+    ///
+    /// Only models are available: the [CacheManager][crate::v1::server::cache_manager::CacheManager]
+    /// and [FileManager][crate::v1::server::file_manager::FileManager] target the Gemini Developer API,
+    /// and files are referenced by their `gs://` URI instead.
+    VertexAI {
+        /// The Google Cloud project ID.
+        project: String,
+
+        /// The region of the endpoint, e.g. `us-central1`.
+        location: String,
+    },
+}
+
+impl Backend {
+    pub fn vertex_ai(project: impl Into<String>, location: impl Into<String>) -> Self {
+        Self::VertexAI {
+            project: project.into(),
+            location: location.into(),
+        }
+    }
+
+    /// The URL of a resource, e.g. `models/gemini-1.5-flash:generateContent`.
+    pub(crate) fn url(&self, request_options: &RequestOptions, path: &str) -> String {
+        format!(
+            "{}/{}/{path}",
+            self.base_url(request_options),
+            self.api_version(request_options)
+        )
+    }
+
+    /// [RequestOptions::base_url], or the default endpoint of the backend.
+    pub(crate) fn base_url(&self, request_options: &RequestOptions) -> String {
+        match (&request_options.base_url, self) {
+            (Some(base_url), _) => base_url.clone(),
+            (None, Self::GeminiDeveloperApi) => {
+                String::from("https://generativelanguage.googleapis.com")
+            }
+            (None, Self::VertexAI { location, .. }) => {
+                format!("https://{location}-aiplatform.googleapis.com")
+            }
+        }
+    }
+
+    /// The version segment of the URLs. [ApiVersion::V1Beta] is `v1beta1` on Vertex AI.
+    pub(crate) fn api_version(&self, request_options: &RequestOptions) -> &'static str {
+        let api_version = request_options.api_version.clone().unwrap_or_default();
+        match (self, api_version) {
+            (Self::VertexAI { .. }, ApiVersion::V1Beta) => "v1beta1",
+            (_, api_version) => api_version.to_str(),
+        }
+    }
+
+    /// The resource name of a model, given as `models/{model}` or `{model}`.
+    ///
+    /// On Vertex AI, Google models are published under `projects/{project}/locations/{location}/publishers/google/models/{model}`.
+    /// Names already starting with `projects/`, e.g. tuned model endpoints, are kept as is.
+    pub(crate) fn model_name(&self, model: &str) -> String {
+        match self {
+            Self::GeminiDeveloperApi if model.contains('/') => model.to_string(),
+            Self::GeminiDeveloperApi => format!("models/{model}"),
+            Self::VertexAI { .. } if model.starts_with("projects/") => model.to_string(),
+            Self::VertexAI { project, location } => format!(
+                "projects/{project}/locations/{location}/publishers/google/models/{}",
+                model.trim_start_matches("models/")
+            ),
+        }
+    }
+
+    /// The collection listing the models, `publishers/google/models` on Vertex AI.
+    pub(crate) fn models_path(&self) -> &'static str {
+        match self {
+            Self::GeminiDeveloperApi => "models",
+            Self::VertexAI { .. } => "publishers/google/models",
+        }
+    }
+}
+
 impl Default for RequestOptions {
     fn default() -> Self {
         Self {
             timeout: None,
             api_version: Some(ApiVersion::default()),
             api_client: None,
-            base_url: Some(String::from("https://generativelanguage.googleapis.com")),
+            base_url: None,
             custom_headers: None,
+            retry_policy: None,
         }
     }
 }
 
 /// GenerateContentRequest: Request to generate a completion from the model.
 #[derive(Debug, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct GenerateContentRequest {
     /// The name of the `Model` to use for generating the completion.
     /// Format: `name=models/{model}`.
@@ -102,7 +233,243 @@ pub struct GenerateContentRequest {
     /// Note: only used in explicit caching, where users can have control over caching (e.g. what content to cache) and enjoy guaranteed cost savings.
     /// Format: `cachedContents/{cachedContent}`
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub cached_content: Option<CachedContent>,
+    pub cached_content: Option<String>,
+}
+
+/// CountTokensRequest: Request to count the number of tokens of an input, as the model would see it.
+///
+/// Only one of [CountTokensRequest::contents] and [CountTokensRequest::generate_content_request] may be set.
+#[derive(Debug, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CountTokensRequest {
+    /// The input given to the model as a prompt.
+    /// This field is ignored when `generate_content_request` is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contents: Option<Vec<Content>>,
+
+    /// The overall input given to the model, including the prompt, system instruction, tools and cached content.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generate_content_request: Option<GenerateContentRequest>,
+}
+
+/// VertexCountTokensRequest is the body of a `countTokens` request on [Backend::VertexAI],
+/// which takes the fields of a [GenerateContentRequest] at the top level instead of wrapping it.
+///
+/// Vertex AI has no equivalent of [GenerateContentRequest::cached_content], which is not counted.
+#[derive(Debug, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct VertexCountTokensRequest {
+    pub contents: Vec<Content>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_instruction: Option<Content>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<Tool>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generation_config: Option<GenerationConfig>,
+}
+
+impl From<CountTokensRequest> for VertexCountTokensRequest {
+    fn from(request: CountTokensRequest) -> Self {
+        match request.generate_content_request {
+            Some(request) => Self {
+                contents: request.contents,
+                system_instruction: request.system_instruction,
+                tools: request.tools,
+                generation_config: request.generation_config,
+            },
+            None => Self {
+                contents: request.contents.unwrap_or_default(),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Input accepted by [count_tokens][crate::v1::models::generative_models::GenerativeModel::count_tokens].
+#[derive(Debug)]
+pub enum CountTokensInput {
+    /// Parts of a single user turn.
+    Parts(Vec<Part>),
+
+    /// A whole conversation.
+    Contents(Vec<Content>),
+
+    /// A complete request, counted as is.
+    /// An empty [GenerateContentRequest::model] defaults to the model counting the tokens.
+    GenerateContentRequest(Box<GenerateContentRequest>),
+}
+
+impl From<Vec<Part>> for CountTokensInput {
+    fn from(parts: Vec<Part>) -> Self {
+        Self::Parts(parts)
+    }
+}
+
+impl From<Vec<Content>> for CountTokensInput {
+    fn from(contents: Vec<Content>) -> Self {
+        Self::Contents(contents)
+    }
+}
+
+impl From<GenerateContentRequest> for CountTokensInput {
+    fn from(request: GenerateContentRequest) -> Self {
+        Self::GenerateContentRequest(Box::new(request))
+    }
+}
+
+/// EmbedContentRequest: Request containing the [Content] for the model to embed.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbedContentRequest {
+    /// The model's resource name. This serves as an ID for the Model to use.
+    /// Format: `models/{model}`.
+    /// Defaults to the model handling the request when left empty.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub model: String,
+
+    /// The content to embed. Only the `parts.text` fields will be counted.
+    pub content: Content,
+
+    /// Optional task type for which the embeddings will be used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_type: Option<TaskType>,
+
+    /// An optional title for the text.
+    /// Only applicable when [EmbedContentRequest::task_type] is [TaskType::RetrievalDocument].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// Optional reduced dimension for the output embedding.
+    /// If set, excessive values in the output embedding are truncated from the end.
+    /// Supported by newer models since 2024 only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_dimensionality: Option<u32>,
+}
+
+impl From<Content> for EmbedContentRequest {
+    fn from(content: Content) -> Self {
+        Self {
+            model: String::new(),
+            content,
+            task_type: None,
+            title: None,
+            output_dimensionality: None,
+        }
+    }
+}
+
+impl From<Vec<Part>> for EmbedContentRequest {
+    fn from(parts: Vec<Part>) -> Self {
+        Content::from(parts).into()
+    }
+}
+
+/// BatchEmbedContentsRequest: Batch request to get embeddings from the model for a list of prompts.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BatchEmbedContentsRequest {
+    /// Embed requests for the batch.
+    /// The model in each of these requests must match the model handling the batch.
+    pub requests: Vec<EmbedContentRequest>,
+}
+
+/// Maximum number of requests accepted by a single batchEmbedContents call.
+pub const MAX_BATCH_EMBED_REQUESTS: usize = 100;
+
+/// Params passed to [embed_many][crate::v1::models::generative_models::GenerativeModel::embed_many].
+#[derive(Debug, Clone)]
+pub struct EmbedManyParams {
+    /// Task type applied to every text.
+    pub task_type: Option<TaskType>,
+
+    /// Optional reduced dimension for the output embeddings.
+    pub output_dimensionality: Option<u32>,
+
+    /// Number of texts sent per batchEmbedContents call, clamped to `1..=`[MAX_BATCH_EMBED_REQUESTS].
+    pub batch_size: usize,
+
+    /// Maximum number of batches in flight at the same time. Defaults to 4.
+    pub concurrency: usize,
+}
+
+impl Default for EmbedManyParams {
+    fn default() -> Self {
+        Self {
+            task_type: None,
+            output_dimensionality: None,
+            batch_size: MAX_BATCH_EMBED_REQUESTS,
+            concurrency: 4,
+        }
+    }
+}
+
+/// Params passed to [CacheManager::list][crate::v1::server::cache_manager::CacheManager::list]
+/// and [FileManager::list_files][crate::v1::server::file_manager::FileManager::list_files].
+#[derive(Debug, Default, Clone)]
+pub struct ListParams {
+    /// The maximum number of items to return.
+    /// The service may return fewer than this value. If unspecified, some default (under maximum) number of items will be returned.
+    pub page_size: Option<u32>,
+
+    /// A page token, received from a previous list call.
+    /// Provide this to retrieve the subsequent page.
+    pub page_token: Option<String>,
+}
+
+impl ListParams {
+    /// The `pageSize` and `pageToken` query parameters of a list call.
+    pub(crate) fn into_query(self) -> Vec<(&'static str, String)> {
+        let mut query = Vec::new();
+        if let Some(page_size) = self.page_size {
+            query.push(("pageSize", page_size.to_string()));
+        }
+        if let Some(page_token) = self.page_token {
+            query.push(("pageToken", page_token));
+        }
+        query
+    }
+}
+
+/// TaskType is the type of task for which the embedding will be used.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub enum TaskType {
+    /// Unset value, which will default to one of the other enum values.
+    #[serde(rename = "TASK_TYPE_UNSPECIFIED")]
+    TaskTypeUnspecified,
+
+    /// Specifies the given text is a query in a search/retrieval setting.
+    #[serde(rename = "RETRIEVAL_QUERY")]
+    RetrievalQuery,
+
+    /// Specifies the given text is a document from the corpus being searched.
+    #[serde(rename = "RETRIEVAL_DOCUMENT")]
+    RetrievalDocument,
+
+    /// Specifies the given text will be used for STS.
+    #[serde(rename = "SEMANTIC_SIMILARITY")]
+    SemanticSimilarity,
+
+    /// Specifies that the given text will be classified.
+    #[serde(rename = "CLASSIFICATION")]
+    Classification,
+
+    /// Specifies that the embeddings will be used for clustering.
+    #[serde(rename = "CLUSTERING")]
+    Clustering,
+
+    /// Specifies that the given text will be used for question answering.
+    #[serde(rename = "QUESTION_ANSWERING")]
+    QuestionAnswering,
+
+    /// Specifies that the given text will be used for fact verification.
+    #[serde(rename = "FACT_VERIFICATION")]
+    FactVerification,
+
+    /// A value unknown to this version of the SDK, kept as is.
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 pub enum Task {
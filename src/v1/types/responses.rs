@@ -12,27 +12,38 @@ use super::{
 /// - either all requested candidates are returned or no candidates at all
 /// - no candidates are returned only if there was something wrong with the prompt (see `prompt_feedback`)
 /// - feedback on each candidate is reported on `finish_reason` and `safety_ratings`.
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GenerateContentResponse {
     /// Candidate responses from the model.
+    /// Empty when the prompt was blocked, and in some chunks of a streamed response.
+    #[serde(default)]
     pub candidates: Vec<Candidate>,
 
     /// Returns the prompt's feedback related to the content filters.
     pub prompt_feedback: Option<PromptFeedback>,
+
     /// Output only. Metadata on the generation requests' token usage.
-    pub usage_metadata: UsageMetadata,
+    /// Omitted from some chunks of a streamed response.
+    pub usage_metadata: Option<UsageMetadata>,
+
+    /// Fields unknown to this version of the SDK, kept as is.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// Candidate is a response candidate generated from the model.
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Candidate {
     /// Output only. Index of the candidate in the list of candidates.
+    /// Omitted by the API for the first candidate.
+    #[serde(default)]
     pub index: u32,
 
     /// Output only. Generated content returned from the model.
-    pub content: Content,
+    /// Omitted when the candidate was stopped, e.g. for [FinishReason::Safety].
+    pub content: Option<Content>,
 
     /// Output only. The reason why the model stopped generating tokens.
     ///
@@ -42,6 +53,7 @@ pub struct Candidate {
     /// List of ratings for the safety of a response candidate.
     ///
     /// There is at most one rating per category.
+    #[serde(default)]
     pub safety_ratings: Vec<SafetyRating>,
 
     /// Output only. Citation information for model-generated candidate.
@@ -50,10 +62,14 @@ pub struct Candidate {
     /// These are passages that are "recited" from copyrighted material in the foundational LLM's training data.
     pub citation_metadata: Option<CitationMetadata>,
     pub token_count: Option<u32>,
+
+    /// Fields unknown to this version of the SDK, kept as is.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// FinishReason is defines the reason why the model stopped generating tokens.
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum FinishReason {
     /// Default value. This value is unused.
     #[serde(rename = "FINISH_REASON_UNSPECIFIED")]
@@ -82,13 +98,33 @@ pub enum FinishReason {
     /// Unknown reason.
     #[serde(rename = "OTHER")]
     Other,
+
+    /// The candidate content contained forbidden terms.
+    #[serde(rename = "BLOCKLIST")]
+    Blocklist,
+
+    /// The candidate content potentially contained prohibited content.
+    #[serde(rename = "PROHIBITED_CONTENT")]
+    ProhibitedContent,
+
+    /// The candidate content potentially contained Sensitive Personally Identifiable Information (SPII).
+    #[serde(rename = "SPII")]
+    Spii,
+
+    /// The function call generated by the model is invalid.
+    #[serde(rename = "MALFORMED_FUNCTION_CALL")]
+    MalformedFunctionCall,
+
+    /// A value unknown to this version of the SDK, kept as is.
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 /// SafetyRating is the safety rating for a piece of content.
 ///
 /// The safety rating contains the category of harm and the harm probability level in that category for a piece of content.
 /// Content is classified for safety across a number of harm categories and the probability of the harm classification is included here.
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct SafetyRating {
     /// The category for this rating.
     pub category: HarmCategory,
@@ -98,18 +134,23 @@ pub struct SafetyRating {
 
     /// Was this content blocked because of this rating?
     pub blocked: Option<bool>,
+
+    /// Fields unknown to this version of the SDK, kept as is.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// CitationMetadata is a collection of source attributions for a piece of content.
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CitationMetadata {
     /// Citations to sources for a specific response.
+    #[serde(default)]
     pub citation_sources: Vec<CitationSource>,
 }
 
 /// CitationSource contains a citation to a source for a portion of a specific response.
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CitationSource {
     /// Start of segment of the response that is attributed to this source.
@@ -127,24 +168,33 @@ pub struct CitationSource {
     ///
     /// License info is required for code citations.
     pub license: Option<String>,
+
+    /// Fields unknown to this version of the SDK, kept as is.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// PromptFeedback contains a set of the feedback metadata the prompt specified in [GenerateContentRequest::contents][crate::v1::types::requests::GenerateContentRequest::contents]
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PromptFeedback {
-    /// The prompt was blocked and no candidates are returned. Rephrase your prompt.
-    pub block_reason: BlockReason,
+    /// If set, the prompt was blocked and no candidates are returned. Rephrase your prompt.
+    pub block_reason: Option<BlockReason>,
 
     /// Ratings for safety of the prompt.
     /// There is at most one rating per category.
+    #[serde(default)]
     pub safety_ratings: Vec<SafetyRating>,
 
     pub block_reason_message: Option<String>,
+
+    /// Fields unknown to this version of the SDK, kept as is.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// BlockReason is specifies what was the reason why prompt was blocked.
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum BlockReason {
     /// A blocked reason was not specified.
     #[serde(rename = "BLOCKED_REASON_UNSPECIFIED")]
@@ -157,28 +207,92 @@ pub enum BlockReason {
     /// Content was blocked, but the reason is uncategorized.
     #[serde(rename = "OTHER")]
     Other,
+
+    /// Prompt was blocked due to the terms which are included from the terminology blocklist.
+    #[serde(rename = "BLOCKLIST")]
+    Blocklist,
+
+    /// Prompt was blocked due to prohibited content.
+    #[serde(rename = "PROHIBITED_CONTENT")]
+    ProhibitedContent,
+
+    /// A value unknown to this version of the SDK, kept as is.
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 /// UsageMetadata is metadata on the generation request's token usage.
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UsageMetadata {
     /// Number of tokens in the prompt.
     /// When cached_content is set, this is still the total effective prompt size.
     /// I.e. this includes the number of tokens in the cached content.
+    #[serde(default)]
     pub prompt_token_count: u32,
 
-    /// Total number of tokens across the generated candidates.
+    /// Total number of tokens across the generated candidates. Zero when the prompt was blocked.
+    #[serde(default)]
     pub candidates_token_count: u32,
 
     /// Total token count for the generation request (prompt + candidates).
+    #[serde(default)]
     pub total_token_count: u32,
 
     /// Number of tokens in the cached part of the prompt, i.e. in the cached content.
     pub cached_content_token_count: Option<u32>,
+
+    /// Fields unknown to this version of the SDK, kept as is.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// CountTokensResponse is the response from a [count_tokens][crate::v1::models::generative_models::GenerativeModel::count_tokens] call.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CountTokensResponse {
+    /// The number of tokens that the model tokenizes the prompt into.
+    /// Always non-negative. When cached_content is set, this is still the total effective prompt size.
+    #[serde(default)]
+    pub total_tokens: u32,
+
+    /// Number of tokens in the cached part of the prompt, i.e. in the cached content.
+    pub cached_content_token_count: Option<u32>,
+
+    /// Fields unknown to this version of the SDK, kept as is.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+/// EmbedContentResponse is the response from an [embed_content][crate::v1::models::generative_models::GenerativeModel::embed_content] call.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct EmbedContentResponse {
+    /// The embedding generated from the input content.
+    pub embedding: ContentEmbedding,
+}
+
+/// BatchEmbedContentsResponse is the response from a [batch_embed_contents][crate::v1::models::generative_models::GenerativeModel::batch_embed_contents] call.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct BatchEmbedContentsResponse {
+    /// The embeddings for each request, in the same order as provided in the batch request.
+    pub embeddings: Vec<ContentEmbedding>,
+}
+
+/// ContentEmbedding is a list of floats representing an embedding.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ContentEmbedding {
+    /// The embedding values.
+    pub values: Vec<f32>,
+}
+
+/// ListModelResponse is the response from [get_model_list][crate::v1::genai::GoogleGenerativeAI::get_model_list].
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ListModelResponse {
+    /// The models, listed as `publisherModels` by Vertex AI.
+    #[serde(default, alias = "publisherModels")]
     pub models: Vec<Model>,
+
+    /// A token to retrieve the next page. If this field is omitted, there are no subsequent pages.
+    pub next_page_token: Option<String>,
 }
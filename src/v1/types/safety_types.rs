@@ -1,5 +1,5 @@
 /// Harm categories that would cause prompts or candidates to be blocked.
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum HarmCategory {
     #[serde(rename = "HARM_CATEGORY_UNSPECIFIED")]
     HarmCategoryUnspecified,
@@ -15,13 +15,20 @@ pub enum HarmCategory {
 
     #[serde(rename = "HARM_CATEGORY_DANGEROUS_CONTENT")]
     HarmCategoryDangerousContent,
+
+    #[serde(rename = "HARM_CATEGORY_CIVIC_INTEGRITY")]
+    HarmCategoryCivicIntegrity,
+
+    /// A value unknown to this version of the SDK, kept as is.
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 /// HarmProbability specifies the probability that a piece of content is harmful.
 ///
 /// The classification system gives the probability of the content being unsafe.
 /// This does not indicate the severity of harm for a piece of content.
-#[derive(Debug, Clone, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum HarmProbability {
     /// Probability is unspecified.
     #[serde(rename = "HARM_PROBABILITY_UNSPECIFIED")]
@@ -42,30 +49,42 @@ pub enum HarmProbability {
     /// Content has a high chance of being unsafe.
     #[serde(rename = "HIGH")]
     High,
+
+    /// A value unknown to this version of the SDK, kept as is.
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 /// Threshold above which a prompt or candidate will be blocked.
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum HarmBlockThreshold {
     /// Threshold is unspecified.
-    // #[serde(rename = "HARM_BLOCK_THRESHOLD_UNSPECIFIED")]
+    #[serde(rename = "HARM_BLOCK_THRESHOLD_UNSPECIFIED")]
     HarmBlockThresholdUnspecified,
 
     /// Content with NEGLIGIBLE will be allowed.
-    // #[serde(rename = "BLOCK_LOW_AND_ABOVE")]
+    #[serde(rename = "BLOCK_LOW_AND_ABOVE")]
     BlockLowAndAbove,
 
     /// Content with NEGLIGIBLE and LOW will be allowed.
-    // #[serde(rename = "BLOCK_MEDIUM_AND_ABOVE")]
+    #[serde(rename = "BLOCK_MEDIUM_AND_ABOVE")]
     BlockMediumAndAbove,
 
     /// Content with NEGLIGIBLE, LOW, and MEDIUM will be allowed.
-    // #[serde(rename = "BLOCK_ONLY_HIGH")]
+    #[serde(rename = "BLOCK_ONLY_HIGH")]
     BlockOnlyHigh,
 
     /// All content will be allowed.
-    // #[serde(rename = "BLOCK_NONE")]
+    #[serde(rename = "BLOCK_NONE")]
     BlockNone,
+
+    /// Turn off the safety filter.
+    #[serde(rename = "OFF")]
+    Off,
+
+    /// A value unknown to this version of the SDK, kept as is.
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 /// Safety setting that can be sent as part of request parameters.
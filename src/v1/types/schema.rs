@@ -3,7 +3,7 @@ use std::collections::HashMap;
 /// Schema is the [Schema] object allows the definition of input and output data types.
 /// These types can be objects, but also primitives and arrays.
 /// Represents a select subset of an [OpenAPI 3.0 schema object](https://spec.openapis.org/oas/v3.0.3#schema).
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Schema {
     /// The type of the property.
     pub r#type: SchemaType,
@@ -13,14 +13,17 @@ pub struct Schema {
     /// Supported formats:
     /// - NUMBER: float, double
     /// - INTEGER: i32, i64
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub format: Option<String>,
 
     /// A brief description of the parameter.
     /// This could contain examples of use.
     /// Parameter description may be formatted as Markdown.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
 
     /// Indicates if the value may be null.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub nullable: Option<bool>,
 
     /// Possible values of the element of [SchemaType::String] with enum format.
@@ -46,28 +49,43 @@ pub struct Schema {
     ///     example: None,
     /// };
     /// ```
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub r#enum: Option<Vec<String>>,
 
     /// Schema of the elements of [SchemaType::Array].
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub items: Option<Box<Schema>>,
 
     /// Properties of [SchemaType::Object].
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub properties: Option<HashMap<String, Schema>>,
 
     /// Required properties of [SchemaType::Object].
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub required: Option<Vec<String>>,
 
     /// The example of the property.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub example: Option<serde_json::Value>,
 }
 
-/// Type contains the list of OpenAPI data types as defined by https://spec.openapis.org/oas/v3.0.3#data-types
-#[derive(Debug, Clone, serde::Serialize)]
+/// Type contains the list of OpenAPI data types as defined by <https://spec.openapis.org/oas/v3.0.3#data-types>
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum SchemaType {
+    #[serde(rename = "STRING")]
     String,
+    #[serde(rename = "NUMBER")]
     Number,
+    #[serde(rename = "INTEGER")]
     Integer,
+    #[serde(rename = "BOOLEAN")]
     Boolean,
+    #[serde(rename = "ARRAY")]
     Array,
+    #[serde(rename = "OBJECT")]
     Object,
+
+    /// A value unknown to this version of the SDK, kept as is.
+    #[serde(untagged)]
+    Unknown(String),
 }
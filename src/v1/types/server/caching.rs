@@ -1,62 +1,140 @@
+use std::time::Duration;
+
 use crate::v1::types::content_types::{Content, Tool, ToolConfig};
 
 /// CachedContent is content that has been preprocessed and can be used in subsequent request to GenerativeService.
 /// Cached content can be only used with model it was created for.
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct CachedContent {
     /// Specifies when this resource will expire.
     /// Types that are assignable to Expiration:
     /// - CachedContent_ExpireTime
     /// - CachedContent_Ttl
+    #[serde(flatten)]
     pub expiration: ExpireTimeOrTTL,
 
     /// Identifier. The resource name referring to the cached content.
     /// Format: `cachedContents/{id}`
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
 
     /// Immutable. The user-generated meaningful display name of the cached content. Maximum 128 Unicode characters.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub display_name: Option<String>,
 
     /// Immutable. The name of the `Model` to use for cached content
     /// Format: `models/{model}`
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub model: String,
 
     /// Input only. Immutable. Developer set system instruction.
     /// Currently text only.
-    pub system_instruction: Content,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_instruction: Option<Content>,
 
-    /// Input only. Immutable. Developer set system instruction.
-    /// Currently text only.
+    /// Input only. Immutable. The content to cache.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub contents: Vec<Content>,
 
     /// Input only. Immutable. A list of `Tools` the model may use to generate the next response
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<Vec<Tool>>,
 
     /// Input only. Immutable. Tool config. This config is shared for all tools.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_config: Option<ToolConfig>,
 
     /// Output only. Creation time of the cache entry.
-    pub create_time: String,
+    #[serde(skip_serializing)]
+    pub create_time: Option<String>,
 
     /// Output only. When the cache entry was last updated in UTC time.
-    pub update_time: String,
+    #[serde(skip_serializing)]
+    pub update_time: Option<String>,
 
     /// Output only. Metadata on the usage of the cached content.
-    pub usage_metadata: CachedContentUsageMetadata,
+    #[serde(skip_serializing)]
+    pub usage_metadata: Option<CachedContentUsageMetadata>,
+}
+
+impl From<&str> for CachedContent {
+    /// Create a handle to an existing cached content from its name, with or without the `cachedContents/` prefix.
+    fn from(name: &str) -> Self {
+        Self {
+            name: Some(cache_name(name)),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<String> for CachedContent {
+    fn from(name: String) -> Self {
+        name.as_str().into()
+    }
+}
+
+/// Prepend `cachedContents/` to a cached content name when missing.
+pub(crate) fn cache_name(name: &str) -> String {
+    if name.starts_with("cachedContents/") {
+        name.to_string()
+    } else {
+        format!("cachedContents/{name}")
+    }
 }
 
 /// ExpireTimeOrTTL describes the time when a resource expires.
 /// If expire_time is non-zero, it is the expiration time.
 /// Otherwise, the expiration time is the value of TTL ("time to live") added to the current time.
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ExpireTimeOrTTL {
-    pub expire_time: String,
-    pub ttl: String,
+    /// Timestamp in UTC of when this resource is considered expired, in RFC 3339 format.
+    /// E.g. `2014-10-02T15:01:23Z`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expire_time: Option<String>,
+
+    /// Input only. New TTL for this resource, a duration in seconds ending with `s`. E.g. `3.5s`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<String>,
+}
+
+impl ExpireTimeOrTTL {
+    /// Expire at the given RFC 3339 timestamp.
+    pub fn expire_time(expire_time: &str) -> Self {
+        Self {
+            expire_time: Some(expire_time.to_string()),
+            ttl: None,
+        }
+    }
+
+    /// Expire after the given duration from now.
+    pub fn ttl(ttl: Duration) -> Self {
+        Self {
+            expire_time: None,
+            ttl: Some(format!("{}s", ttl.as_secs_f64())),
+        }
+    }
 }
 
 /// CachedContentUsageMetadata is metadata on the usage of the cached content.
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct CachedContentUsageMetadata {
     /// Total number of tokens that the cached content consumes.
+    #[serde(default)]
     pub total_token_count: u32,
 }
+
+/// ListCachedContentsResponse is the response from [CacheManager::list][crate::v1::server::cache_manager::CacheManager::list].
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListCachedContentsResponse {
+    /// List of cached contents.
+    #[serde(default)]
+    pub cached_contents: Vec<CachedContent>,
+
+    /// A token, which can be sent as [ListParams::page_token][crate::v1::types::requests::ListParams::page_token] to retrieve the next page.
+    /// If this field is omitted, there are no subsequent pages.
+    pub next_page_token: Option<String>,
+}
@@ -0,0 +1,172 @@
+use crate::v1::types::content_types::{FileData, Part};
+
+/// Metadata to provide alongside a file upload.
+#[derive(Debug, Default, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileMetadata {
+    /// The resource name of the file, e.g. `files/123-456`.
+    /// Generated by the API when left unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// The human-readable display name for the file. Maximum 512 characters.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+
+    /// The IANA standard MIME type of the file.
+    pub mime_type: String,
+}
+
+/// File is a file uploaded to the File API.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct File {
+    /// The resource name of the file. Format: `files/{file}`.
+    pub name: String,
+
+    /// The human-readable display name for the file.
+    pub display_name: Option<String>,
+
+    /// MIME type of the file.
+    pub mime_type: String,
+
+    /// Size of the file in bytes.
+    #[serde(default, deserialize_with = "deserialize_size_bytes")]
+    pub size_bytes: Option<u64>,
+
+    /// Creation time of the file, in RFC 3339 format.
+    pub create_time: Option<String>,
+
+    /// Last update time of the file, in RFC 3339 format.
+    pub update_time: Option<String>,
+
+    /// The time at which the file will be deleted, in RFC 3339 format.
+    pub expiration_time: Option<String>,
+
+    /// SHA-256 hash of the uploaded bytes, base64 encoded.
+    pub sha256_hash: Option<String>,
+
+    /// The URI of the file, to reference it in a [FileData].
+    pub uri: String,
+
+    /// Processing state of the file.
+    #[serde(default)]
+    pub state: FileState,
+
+    /// Error status if the processing of the file failed.
+    pub error: Option<RpcStatus>,
+
+    /// Metadata for a video, set once the video is processed.
+    pub video_metadata: Option<VideoMetadata>,
+
+    /// Fields unknown to this version of the SDK, kept as is.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl File {
+    /// Create a [Part] referencing this file, to include it in a prompt.
+    ///
+    /// The file must be [FileState::Active], see [FileManager::wait_until_active][crate::v1::server::file_manager::FileManager::wait_until_active].
+    pub fn to_part(&self) -> Part {
+        FileData::from(self).into()
+    }
+}
+
+impl From<&File> for FileData {
+    fn from(file: &File) -> Self {
+        Self {
+            mime_type: file.mime_type.clone(),
+            uri: file.uri.clone(),
+            extra: Default::default(),
+        }
+    }
+}
+
+/// FileState is the processing state of a [File].
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum FileState {
+    /// The default value. This value is used if the state is omitted.
+    #[default]
+    #[serde(rename = "STATE_UNSPECIFIED")]
+    StateUnspecified,
+
+    /// File is being processed and cannot be used for inference yet.
+    #[serde(rename = "PROCESSING")]
+    Processing,
+
+    /// File is processed and available for inference.
+    #[serde(rename = "ACTIVE")]
+    Active,
+
+    /// File failed processing.
+    #[serde(rename = "FAILED")]
+    Failed,
+
+    /// A value unknown to this version of the SDK, kept as is.
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+/// RpcStatus is the error status of a failed operation, such as the processing of a [File].
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct RpcStatus {
+    /// The status code, which should be an enum value of `google.rpc.Code`.
+    pub code: Option<i32>,
+
+    /// A developer-facing error message, which should be in English.
+    pub message: Option<String>,
+
+    /// A list of messages that carry the error details.
+    #[serde(default)]
+    pub details: Vec<serde_json::Value>,
+}
+
+/// VideoMetadata is metadata for a video [File].
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoMetadata {
+    /// Duration of the video, a duration in seconds ending with `s`. E.g. `3.5s`.
+    pub video_duration: Option<String>,
+}
+
+/// UploadFileResponse is the response from an upload through the [FileManager][crate::v1::server::file_manager::FileManager].
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct UploadFileResponse {
+    /// Metadata for the created file.
+    pub file: File,
+}
+
+/// ListFilesResponse is the response from [FileManager::list_files][crate::v1::server::file_manager::FileManager::list_files].
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListFilesResponse {
+    /// The list of files.
+    #[serde(default)]
+    pub files: Vec<File>,
+
+    /// A token, which can be sent as [ListParams::page_token][crate::v1::types::requests::ListParams::page_token] to retrieve the next page.
+    /// If this field is omitted, there are no subsequent pages.
+    pub next_page_token: Option<String>,
+}
+
+/// The API encodes `int64` fields as JSON strings.
+fn deserialize_size_bytes<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::Deserialize;
+
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum SizeBytes {
+        String(String),
+        Number(u64),
+    }
+
+    match Option::<SizeBytes>::deserialize(deserializer)? {
+        Some(SizeBytes::String(size)) => size.parse().map(Some).map_err(serde::de::Error::custom),
+        Some(SizeBytes::Number(size)) => Ok(Some(size)),
+        None => Ok(None),
+    }
+}